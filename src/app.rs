@@ -9,22 +9,29 @@ use tui::terminal::Frame;
 use crate::config::AppConfig;
 use crate::datatypes::game_version::GameVersion;
 use crate::datatypes::server::Address;
-use crate::input::UserInput;
+use crate::input::{Redraw, UserInput};
 use crate::states::app::AppState;
 #[cfg(feature = "geolocation")]
 use crate::views::world::World;
-use crate::views::{events::EventsView, help::Help, tabs::TabView, AppView, Drawable, ViewType};
+use crate::views::{
+    cleanup::Cleanup, confirm::Confirm, event_log::EventLog, events::EventsView, game_log::GameLog,
+    help::Help, tabs::TabView, AppView, Drawable, ViewType,
+};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum AppAction {
     // view management
     OpenView(ViewType),
     CloseView,
+    // closes confirmation view and runs action without asking again
+    Confirmed(Box<AppAction>),
     // installations
     InstallVersion(GameVersion),
     AbortVersionInstallation(GameVersion),
     UninstallVersion(GameVersion),
     LaunchVersion(GameVersion),
+    VerifyVersion(GameVersion),
+    RepairVersion(GameVersion),
     ConnectToServer {
         version: GameVersion,
         address: Address,
@@ -44,9 +51,9 @@ pub struct App {
 }
 
 impl App {
-    pub async fn new(config: AppConfig) -> Self {
+    pub async fn new(config: AppConfig, redraw: Arc<Redraw>) -> Self {
         let panic_bool = Arc::new(AtomicBool::new(false));
-        let state = AppState::new(config, panic_bool.clone()).await;
+        let state = AppState::new(config, panic_bool.clone(), redraw).await;
 
         let mut instance = Self {
             state,
@@ -64,6 +71,10 @@ impl App {
         #[cfg(feature = "geolocation")]
         instance.register_view(ViewType::World, Box::new(World {}));
         instance.register_view(ViewType::Help, Box::new(Help {}));
+        instance.register_view(ViewType::Cleanup, Box::new(Cleanup::new()));
+        instance.register_view(ViewType::Confirm, Box::new(Confirm {}));
+        instance.register_view(ViewType::Events, Box::new(EventLog::new()));
+        instance.register_view(ViewType::GameLog, Box::new(GameLog::new()));
 
         instance
     }
@@ -100,8 +111,15 @@ impl App {
     pub(crate) async fn on_input(&mut self, input: &UserInput) {
         log::debug!("input: {:?}", input);
 
+        let captures_input = self
+            .view_stack
+            .last()
+            .and_then(|tp| self.views.get(tp))
+            .map_or(false, |view| view.captures_input());
+
         match input {
             UserInput::Quit => self.stop(),
+            UserInput::Char('q' | 'Q') if !captures_input => self.stop(),
             UserInput::Help => {
                 if let Some(top_view_type) = self.view_stack.last() {
                     if top_view_type == &ViewType::Help {
@@ -125,8 +143,21 @@ impl App {
                                 AppAction::CloseView => {
                                     self.view_stack.pop();
                                 }
+                                AppAction::Confirmed(action) => {
+                                    self.view_stack.pop();
 
-                                _ => self.state.on_action(&action, Arc::clone(&self.state)).await,
+                                    self.state.on_action(&action, Arc::clone(&self.state)).await
+                                }
+                                _ => {
+                                    if let Some(confirmation) =
+                                        self.state.confirmation(&action).await
+                                    {
+                                        self.state.confirm.lock().unwrap().set(confirmation);
+                                        self.view_stack.push(ViewType::Confirm);
+                                    } else {
+                                        self.state.on_action(&action, Arc::clone(&self.state)).await
+                                    }
+                                }
                             }
                         }
                     }
@@ -0,0 +1,489 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context};
+
+use bytesize::ByteSize;
+
+use clap::Subcommand;
+
+use serde::Serialize;
+
+use crate::config::AppConfig;
+use crate::datatypes::{
+    export::ExportFormat,
+    game_version::{DownloadUrl, GameVersion},
+    installation::{Installation, InstallationKind},
+    server::{find_ignore_case, Server},
+};
+use crate::states::{AppState, ServersState, VersionsState};
+
+// 2 is used by clap for bad arguments
+const EXIT_FAILURE: i32 = 1;
+const EXIT_NOT_FOUND: i32 = 3;
+const EXIT_NETWORK: i32 = 4;
+
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Print server list
+    Servers {
+        /// Print JSON instead of table
+        #[clap(long)]
+        json: bool,
+    },
+    /// Export server list with installation and location info
+    Export {
+        /// json, csv or ndjson
+        #[clap(long, default_value = "json")]
+        format: ExportFormat,
+        /// Write to file instead of stdout
+        #[clap(long)]
+        output: Option<PathBuf>,
+    },
+    /// Print installed versions and versions available from servers
+    Versions {
+        /// Print JSON instead of table
+        #[clap(long)]
+        json: bool,
+    },
+    /// Download and install version
+    Install { fork: String, build: String },
+    /// Remove installed version
+    Uninstall { fork: String, build: String },
+    /// Check installed files against list recorded during installation
+    Verify {
+        fork: String,
+        build: String,
+        /// Download and install version again if files are damaged
+        #[clap(long)]
+        repair: bool,
+    },
+    /// Run version, installing it first if needed
+    Launch { fork: String, build: String },
+    /// Connect to server by name or address (ip:port), installing its version if needed
+    Connect { server: String },
+}
+
+enum Failure {
+    NotFound(String),
+    Network(anyhow::Error),
+    Other(anyhow::Error),
+}
+
+impl Failure {
+    const fn exit_code(&self) -> i32 {
+        match self {
+            Self::NotFound(_) => EXIT_NOT_FOUND,
+            Self::Network(_) => EXIT_NETWORK,
+            Self::Other(_) => EXIT_FAILURE,
+        }
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NotFound(what) => write!(f, "{}", what),
+            Self::Network(err) | Self::Other(err) => write!(f, "{:#}", err),
+        }
+    }
+}
+
+impl From<anyhow::Error> for Failure {
+    fn from(err: anyhow::Error) -> Self {
+        Self::Other(err)
+    }
+}
+
+#[derive(Serialize)]
+struct VersionInfo<'a> {
+    fork: &'a str,
+    build: &'a str,
+    status: &'static str,
+    size: Option<u64>,
+}
+
+impl<'a> From<&'a Installation> for VersionInfo<'a> {
+    fn from(installation: &'a Installation) -> Self {
+        Self {
+            fork: &installation.version.fork,
+            build: &installation.version.build,
+            status: match installation.kind {
+                InstallationKind::Discovered => "discovered",
+                InstallationKind::Queued { .. } => "queued",
+                InstallationKind::Downloading { .. } => "downloading",
+                InstallationKind::Unpacking => "unpacking",
+                InstallationKind::Installed { .. } => "installed",
+                InstallationKind::Broken { .. } => "broken",
+            },
+            size: match installation.kind {
+                InstallationKind::Installed { size, .. } => Some(size),
+                _ => None,
+            },
+        }
+    }
+}
+
+// runs command without tui, returns exit code
+pub async fn run(config: AppConfig, command: Command) -> i32 {
+    let app = match AppState::new_headless(config).await {
+        Ok(app) => app,
+        Err(err) => {
+            eprintln!("error: {:#}", err);
+            return EXIT_FAILURE;
+        }
+    };
+
+    let result = match command {
+        Command::Servers { json } => servers(app, json).await,
+        Command::Export { format, output } => export(app, format, output).await,
+        Command::Versions { json } => versions(app, json).await,
+        Command::Install { fork, build } => install(app, fork, build).await,
+        Command::Uninstall { fork, build } => uninstall(app, fork, build).await,
+        Command::Verify {
+            fork,
+            build,
+            repair,
+        } => verify(app, fork, build, repair).await,
+        Command::Launch { fork, build } => launch(app, fork, build).await,
+        Command::Connect { server } => connect(app, &server).await,
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(failure) => {
+            eprintln!("error: {}", failure);
+            failure.exit_code()
+        }
+    }
+}
+
+async fn fetch_servers(app: &Arc<AppState>) -> Result<(), Failure> {
+    if app.config.offline {
+        return Err(Failure::Network(anyhow!(
+            "Server list is not available in offline mode"
+        )));
+    }
+
+    ServersState::fetch(app.clone())
+        .await
+        .map_err(Failure::Network)
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<(), Failure> {
+    println!(
+        "{}",
+        serde_json::to_string_pretty(value).map_err(anyhow::Error::from)?
+    );
+
+    Ok(())
+}
+
+async fn servers(app: Arc<AppState>, json: bool) -> Result<(), Failure> {
+    fetch_servers(&app).await?;
+
+    if json {
+        return print_json(&ServersState::export_records(&app).await);
+    }
+
+    let servers = app.servers.read().await;
+
+    let name_width = servers
+        .items
+        .iter()
+        .map(|s| s.name.chars().count())
+        .max()
+        .unwrap_or_default();
+
+    for server in &servers.items {
+        println!(
+            "{:name_width$}  {:>3}  {:24}  {:20}  {}{}",
+            server.name,
+            server.players,
+            format!("{}-{}", server.version.fork, server.version.build),
+            server.map,
+            server.address,
+            if server.offline { "  offline" } else { "" },
+            name_width = name_width
+        );
+    }
+
+    Ok(())
+}
+
+async fn export(
+    app: Arc<AppState>,
+    format: ExportFormat,
+    output: Option<PathBuf>,
+) -> Result<(), Failure> {
+    fetch_servers(&app).await?;
+
+    let data = format.export(&ServersState::export_records(&app).await)?;
+
+    match output {
+        Some(path) => std::fs::write(&path, data)
+            .with_context(|| format!("Unable to write {}", path.display()))?,
+        None => print!("{}", data),
+    }
+
+    Ok(())
+}
+
+async fn versions(app: Arc<AppState>, json: bool) -> Result<(), Failure> {
+    // installed versions are still useful without server list
+    if let Err(failure) = fetch_servers(&app).await {
+        eprintln!("warning: {}", failure);
+    }
+
+    let versions = app.versions.read().await;
+
+    if json {
+        return print_json(
+            &versions
+                .items
+                .iter()
+                .map(VersionInfo::from)
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    for installation in versions.items.iter() {
+        let info = VersionInfo::from(installation);
+
+        println!(
+            "{:32}  {:12}  {}",
+            format!("{}-{}", info.fork, info.build),
+            info.status,
+            info.size
+                .map(|s| ByteSize::b(s).to_string())
+                .unwrap_or_default()
+        );
+    }
+
+    Ok(())
+}
+
+async fn find_version(
+    app: &Arc<AppState>,
+    fork: String,
+    build: String,
+) -> Result<Installation, Failure> {
+    let version = GameVersion {
+        fork,
+        build,
+        download: DownloadUrl::Local,
+    };
+
+    let installed = matches!(
+        app.versions.read().await.items.get(&version),
+        Some(Installation {
+            kind: InstallationKind::Installed { .. },
+            ..
+        })
+    );
+
+    // download url is only known from server list
+    if !installed {
+        fetch_servers(app).await?;
+    }
+
+    app.versions
+        .read()
+        .await
+        .items
+        .get(&version)
+        .cloned()
+        .ok_or_else(|| Failure::NotFound(format!("Version {} not found", version)))
+}
+
+// prints download progress until installation ends
+async fn print_progress(app: Arc<AppState>, version: GameVersion) {
+    loop {
+        tokio::time::sleep(PROGRESS_INTERVAL).await;
+
+        match app
+            .versions
+            .read()
+            .await
+            .items
+            .get(&version)
+            .map(|i| &i.kind)
+        {
+            Some(InstallationKind::Downloading {
+                progress,
+                total,
+                speed,
+            }) => {
+                let total = total.map_or_else(|| "?".to_owned(), |t| ByteSize::b(t).to_string());
+                let speed = speed.map_or_else(String::new, |s| format!(" {}/s", ByteSize::b(s)));
+
+                eprintln!(
+                    "downloading {}: {} / {}{}",
+                    version,
+                    ByteSize::b(*progress),
+                    total,
+                    speed
+                );
+            }
+            Some(InstallationKind::Queued { .. }) => eprintln!("{} is queued", version),
+            _ => {}
+        }
+    }
+}
+
+async fn install_with_progress(app: &Arc<AppState>, version: &GameVersion) -> Result<(), Failure> {
+    tokio::select! {
+        result = VersionsState::wait_for_installation(app.clone(), version.clone()) => result?,
+        _ = print_progress(app.clone(), version.clone()) => {}
+    }
+
+    Ok(())
+}
+
+async fn install(app: Arc<AppState>, fork: String, build: String) -> Result<(), Failure> {
+    let installation = find_version(&app, fork, build).await?;
+
+    if let InstallationKind::Installed { .. } = installation.kind {
+        eprintln!("{} is already installed", installation.version);
+        return Ok(());
+    }
+
+    install_with_progress(&app, &installation.version).await
+}
+
+async fn uninstall(app: Arc<AppState>, fork: String, build: String) -> Result<(), Failure> {
+    let version = GameVersion {
+        fork,
+        build,
+        download: DownloadUrl::Local,
+    };
+
+    if !matches!(
+        app.versions.read().await.items.get(&version),
+        Some(Installation {
+            kind: InstallationKind::Installed { .. },
+            ..
+        })
+    ) {
+        return Err(Failure::NotFound(format!("{} is not installed", version)));
+    }
+
+    VersionsState::uninstall(app, version).await?;
+
+    Ok(())
+}
+
+async fn verify(
+    app: Arc<AppState>,
+    fork: String,
+    build: String,
+    repair: bool,
+) -> Result<(), Failure> {
+    let version = GameVersion {
+        fork,
+        build,
+        download: DownloadUrl::Local,
+    };
+
+    if !matches!(
+        app.versions.read().await.items.get(&version),
+        Some(Installation {
+            kind: InstallationKind::Installed { .. } | InstallationKind::Broken { .. },
+            ..
+        })
+    ) {
+        return Err(Failure::NotFound(format!("{} is not installed", version)));
+    }
+
+    if repair {
+        tokio::select! {
+            result = VersionsState::repair(app.clone(), version.clone()) => result?,
+            _ = print_progress(app.clone(), version) => {}
+        }
+
+        return Ok(());
+    }
+
+    let verification = VersionsState::verify(&app, &version).await?;
+
+    for line in verification.details() {
+        println!("{}", line);
+    }
+
+    if verification.is_intact() {
+        eprintln!("{}: {}", version, verification);
+
+        Ok(())
+    } else {
+        Err(Failure::Other(anyhow!(
+            "{} is damaged: {}",
+            version,
+            verification
+        )))
+    }
+}
+
+async fn launch(app: Arc<AppState>, fork: String, build: String) -> Result<(), Failure> {
+    let installation = find_version(&app, fork, build).await?;
+
+    install_with_progress(&app, &installation.version).await?;
+
+    VersionsState::launch(app, installation.version, None).await?;
+
+    Ok(())
+}
+
+// address, then exact name, then unique part of name
+fn find_server<'a>(servers: &'a [Server], query: &str) -> Result<&'a Server, Failure> {
+    if let Some(server) = servers
+        .iter()
+        .find(|s| s.address.to_string() == query)
+        .or_else(|| servers.iter().find(|s| s.name.eq_ignore_ascii_case(query)))
+    {
+        return Ok(server);
+    }
+
+    let matching: Vec<&Server> = servers
+        .iter()
+        .filter(|s| find_ignore_case(&s.name, query).is_some())
+        .collect();
+
+    match matching.as_slice() {
+        [server] => Ok(server),
+        [] => Err(Failure::NotFound(format!("Server {} not found", query))),
+        _ => Err(Failure::NotFound(format!(
+            "Server {} is ambiguous, matches: {}",
+            query,
+            matching
+                .iter()
+                .map(|s| s.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))),
+    }
+}
+
+async fn connect(app: Arc<AppState>, query: &str) -> Result<(), Failure> {
+    fetch_servers(&app).await?;
+
+    let (version, address) = {
+        let servers = app.servers.read().await;
+        let server = find_server(&servers.items, query)?;
+
+        if server.offline {
+            eprintln!("warning: {} is offline", server.name);
+        }
+
+        (server.version.clone(), server.address.clone())
+    };
+
+    install_with_progress(&app, &version).await?;
+
+    VersionsState::launch(app, version, Some(address)).await?;
+
+    Ok(())
+}
@@ -1,18 +1,69 @@
+use std::collections::BTreeMap;
 use std::env;
 use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
 
+use anyhow::{anyhow, bail, Context};
+
+use bytesize::ByteSize;
+
 use clap::Parser;
 
+use serde::{Deserialize, Serialize};
+
+use crate::cli::Command;
 #[cfg(feature = "geolocation")]
 use crate::constants::DEFAULT_GEO_PROVIDER_URL;
+use crate::constants::{
+    DEFAULT_GITHUB_REPO_COMMIT_ENDPOINT_URL, DEFAULT_SERVER_LIST_URL, DEFAULT_TRUSTED_DOWNLOAD,
+};
+use crate::datatypes::export::ExportFormat;
+use crate::datatypes::game_version::TrustedDownload;
+use crate::datatypes::launch_profile::LaunchProfile;
+use crate::datatypes::watch::WatchRule;
+
+const DEFAULT_UPDATE_INTERVAL: u64 = 20;
+const DEFAULT_PING_CONCURRENCY: usize = 8;
+const DEFAULT_MAX_DOWNLOADS: usize = 2;
+const DEFAULT_KEEP_BUILDS: usize = 3;
+const DEFAULT_HISTORY_MAX_SIZE: u64 = 10 * 1024 * 1024;
+const DEFAULT_HISTORY_INTERVAL: u64 = 300;
+const DEFAULT_WATCH_PLAYERS: u32 = 10;
 
 // thanks kalmari
 fn greater_than_5(s: &str) -> Result<u64, String> {
+    let v = s.parse::<u64>().map_err(|e| e.to_string())?;
+
+    validate_update_interval(v)
+}
+
+fn greater_than_0(s: &str) -> Result<usize, String> {
+    let v = s.parse::<usize>().map_err(|e| e.to_string())?;
+
+    validate_greater_than_0(v)
+}
+
+// switches take optional value, --offline=false overrides config file
+fn parse_bool(s: &str) -> Result<bool, String> {
+    match s.to_lowercase().as_str() {
+        "true" | "1" | "yes" | "on" => Ok(true),
+        "false" | "0" | "no" | "off" => Ok(false),
+        _ => Err(format!("Expected true or false, got {}", s)),
+    }
+}
+
+fn validate_greater_than_0(v: usize) -> Result<usize, String> {
+    if v == 0 {
+        Err("Value must be > 0".to_owned())
+    } else {
+        Ok(v)
+    }
+}
+
+fn validate_update_interval(v: u64) -> Result<u64, String> {
     let min_value = 5;
 
-    let v = s.parse::<u64>().map_err(|e| e.to_string())?;
     if v < min_value {
         Err(format!("Value must be >= {}", min_value))
     } else {
@@ -20,28 +71,264 @@ fn greater_than_5(s: &str) -> Result<u64, String> {
     }
 }
 
+// precedence: command line arguments > environment variables > config file > defaults
 #[derive(Parser, Debug)]
 #[clap(version = clap::crate_version!(), about = "UnityStation server hub")]
 struct CliArgs {
+    /// Config file path [default: <config dir>/rshub/config.toml]
+    #[clap(long, env = "RSHUB_CONFIG")]
+    config: Option<PathBuf>,
+    /// Print effective config and exit
+    #[clap(long)]
+    print_config: bool,
     /// Log file path
-    #[clap(short, long)]
+    #[clap(short, long, env = "RSHUB_LOG_FILE")]
     log_file: Option<PathBuf>,
-    /// Server list update interval, in seconds (must be >= 5)
-    #[clap(short, long, default_value = "20", parse(try_from_str = greater_than_5))]
-    update_interval: u64,
-    /// A level of verbosity, and can be used multiple times
+    /// Server list update interval, in seconds (must be >= 5) [default: 20]
+    #[clap(short, long, env = "RSHUB_UPDATE_INTERVAL", parse(try_from_str = greater_than_5))]
+    update_interval: Option<u64>,
+    /// A level of verbosity, and can be used multiple times [env: RSHUB_VERBOSE=<level>]
     #[clap(short, long, parse(from_occurrences))]
     verbose: u32,
-    /// Geolocation provider (ifconfig.co compatible)
+    /// Geolocation provider (ifconfig.co compatible) [default: https://ifconfig.based.computer]
     #[cfg(feature = "geolocation")]
-    #[clap(long, default_value = DEFAULT_GEO_PROVIDER_URL)]
-    geo_provider: reqwest::Url,
+    #[clap(long, env = "RSHUB_GEO_PROVIDER")]
+    geo_provider: Option<reqwest::Url>,
     /// Offline mode
-    #[clap(long)]
-    offline: bool,
+    #[clap(
+        long,
+        env = "RSHUB_OFFLINE",
+        value_name = "BOOL",
+        min_values = 0,
+        require_equals = true,
+        default_missing_value = "true",
+        parse(try_from_str = parse_bool)
+    )]
+    offline: Option<bool>,
     /// Disable download URL verification
-    #[clap(long)]
-    unchecked_downloads: bool,
+    #[clap(
+        long,
+        env = "RSHUB_UNCHECKED_DOWNLOADS",
+        value_name = "BOOL",
+        min_values = 0,
+        require_equals = true,
+        default_missing_value = "true",
+        parse(try_from_str = parse_bool)
+    )]
+    unchecked_downloads: Option<bool>,
+    /// Maximum number of servers pinged at the same time (0 disables ping) [default: 8]
+    #[clap(long, env = "RSHUB_PING_CONCURRENCY")]
+    ping_concurrency: Option<usize>,
+    /// Maximum number of simultaneous downloads, others are queued [default: 2]
+    #[clap(long, env = "RSHUB_MAX_DOWNLOADS", parse(try_from_str = greater_than_0))]
+    max_downloads: Option<usize>,
+    /// Download rate limit shared by all downloads, for example 5MiB (0 means unlimited) [default: 0]
+    #[clap(long, env = "RSHUB_MAX_DOWNLOAD_RATE")]
+    max_download_rate: Option<ByteSize>,
+    /// Number of newest builds per fork kept by cleanup, at least 1 [default: 3]
+    #[clap(long, env = "RSHUB_KEEP_BUILDS", parse(try_from_str = greater_than_0))]
+    keep_builds: Option<usize>,
+    /// Do not ask for confirmation before removing files
+    #[clap(
+        long,
+        env = "RSHUB_NO_CONFIRM",
+        value_name = "BOOL",
+        min_values = 0,
+        require_equals = true,
+        default_missing_value = "true",
+        parse(try_from_str = parse_bool)
+    )]
+    no_confirm: Option<bool>,
+    /// Remove old builds after every installation
+    #[clap(
+        long,
+        env = "RSHUB_AUTO_CLEANUP",
+        value_name = "BOOL",
+        min_values = 0,
+        require_equals = true,
+        default_missing_value = "true",
+        parse(try_from_str = parse_bool)
+    )]
+    auto_cleanup: Option<bool>,
+    /// Maximum size of population history file, oldest samples are removed (0 disables history) [default: 10MiB]
+    #[clap(long, env = "RSHUB_HISTORY_MAX_SIZE")]
+    history_max_size: Option<ByteSize>,
+    /// Population history sample interval, in seconds [default: 300]
+    #[clap(long, env = "RSHUB_HISTORY_INTERVAL", parse(try_from_str = greater_than_5))]
+    history_interval: Option<u64>,
+    /// Player threshold of watch rules created with hotkey (0 means server is online) [default: 10]
+    #[clap(long, env = "RSHUB_WATCH_PLAYERS")]
+    watch_players: Option<u32>,
+    /// Ring terminal bell when watch rule matches
+    #[clap(
+        long,
+        env = "RSHUB_WATCH_BELL",
+        value_name = "BOOL",
+        min_values = 0,
+        require_equals = true,
+        default_missing_value = "true",
+        parse(try_from_str = parse_bool)
+    )]
+    watch_bell: Option<bool>,
+    /// Shell command executed when watch rule matches, server is passed in RSHUB_SERVER_* environment variables
+    #[clap(long, env = "RSHUB_WATCH_COMMAND")]
+    watch_command: Option<String>,
+    /// Server list export format used by hotkey: json, csv or ndjson [default: json]
+    #[clap(long, env = "RSHUB_EXPORT_FORMAT")]
+    export_format: Option<ExportFormat>,
+    /// Server list API endpoint [default: https://api.unitystation.org/serverlist]
+    #[clap(long, env = "RSHUB_SERVER_LIST_URL")]
+    server_list_url: Option<reqwest::Url>,
+    /// GitHub API commits endpoint [default: https://api.github.com/repos/unitystation/unitystation/commits]
+    #[clap(long, env = "RSHUB_COMMITS_URL")]
+    commits_url: Option<reqwest::Url>,
+    /// Trusted download source as scheme://host[:port], can be used multiple times [default: https://unitystationfile.b-cdn.net]
+    #[clap(
+        long = "trusted-download",
+        env = "RSHUB_TRUSTED_DOWNLOADS",
+        use_delimiter = true,
+        multiple_occurrences = true,
+        number_of_values = 1
+    )]
+    trusted_downloads: Vec<TrustedDownload>,
+    /// Run command without starting interface
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+// every value is optional, missing ones are taken from defaults
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    log_file: Option<PathBuf>,
+    update_interval: Option<u64>,
+    verbose: Option<u32>,
+    // not under feature flag to allow sharing config between builds, ignored if disabled
+    geo_provider: Option<String>,
+    offline: Option<bool>,
+    unchecked_downloads: Option<bool>,
+    ping_concurrency: Option<usize>,
+    max_downloads: Option<usize>,
+    max_download_rate: Option<String>,
+    keep_builds: Option<usize>,
+    auto_cleanup: Option<bool>,
+    confirm_destructive: Option<bool>,
+    export_format: Option<String>,
+    history_max_size: Option<String>,
+    history_interval: Option<u64>,
+    watch_players: Option<u32>,
+    watch_bell: Option<bool>,
+    watch_command: Option<String>,
+    server_list_url: Option<String>,
+    commits_url: Option<String>,
+    trusted_downloads: Option<Vec<String>>,
+    default_profile: Option<String>,
+    // tables, must be after values to be serialized
+    fork_profiles: Option<BTreeMap<String, String>>,
+    profiles: Option<BTreeMap<String, LaunchProfile>>,
+    watch: Option<Vec<WatchRule>>,
+}
+
+impl ConfigFile {
+    fn default_path() -> Option<PathBuf> {
+        dirs_next::config_dir().map(|dir| dir.join(env!("CARGO_PKG_NAME")).join("config.toml"))
+    }
+
+    // missing file is only an error if path was set explicitly
+    fn load(path: &Path, required: bool) -> anyhow::Result<Self> {
+        let data = match fs::read_to_string(path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == io::ErrorKind::NotFound && !required => {
+                return Ok(Self::default());
+            }
+            Err(err) => {
+                return Err(err)
+                    .with_context(|| format!("Unable to read config file {}", path.display()));
+            }
+        };
+
+        let config: Self = toml::from_str(&data)
+            .with_context(|| format!("Invalid config file {}", path.display()))?;
+
+        if let Some(update_interval) = config.update_interval {
+            validate_update_interval(update_interval).map_err(|e| {
+                anyhow!(
+                    "Invalid config file {}: update_interval: {}",
+                    path.display(),
+                    e
+                )
+            })?;
+        }
+
+        if let Some(history_interval) = config.history_interval {
+            validate_update_interval(history_interval).map_err(|e| {
+                anyhow!(
+                    "Invalid config file {}: history_interval: {}",
+                    path.display(),
+                    e
+                )
+            })?;
+        }
+
+        for rule in config.watch.iter().flatten() {
+            rule.validate()
+                .map_err(|e| anyhow!("Invalid config file {}: watch: {}", path.display(), e))?;
+        }
+
+        let profiles = config.profiles.clone().unwrap_or_default();
+
+        for (name, profile) in config
+            .default_profile
+            .iter()
+            .map(|p| ("default_profile", p))
+            .chain(
+                config
+                    .fork_profiles
+                    .iter()
+                    .flatten()
+                    .map(|(_, p)| ("fork_profiles", p)),
+            )
+        {
+            if !profiles.contains_key(profile) {
+                bail!(
+                    "Invalid config file {}: {}: unknown profile {}",
+                    path.display(),
+                    name,
+                    profile
+                );
+            }
+        }
+
+        if let Some(max_downloads) = config.max_downloads {
+            validate_greater_than_0(max_downloads).map_err(|e| {
+                anyhow!(
+                    "Invalid config file {}: max_downloads: {}",
+                    path.display(),
+                    e
+                )
+            })?;
+        }
+
+        // 0 would remove every build not used by servers
+        if let Some(keep_builds) = config.keep_builds {
+            validate_greater_than_0(keep_builds).map_err(|e| {
+                anyhow!("Invalid config file {}: keep_builds: {}", path.display(), e)
+            })?;
+        }
+
+        Ok(config)
+    }
+
+    fn parse_url(
+        name: &str,
+        value: &Option<String>,
+        default: &str,
+    ) -> anyhow::Result<reqwest::Url> {
+        let url = value.as_deref().unwrap_or(default);
+
+        reqwest::Url::parse(url)
+            .with_context(|| format!("Invalid config file value {}: {}", name, url))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -50,15 +337,27 @@ pub struct AppDirs {
 
     pub data_dir: PathBuf,
     pub installations_dir: PathBuf,
+    pub downloads_dir: PathBuf,
+    pub staging_dir: PathBuf,
+    pub exports_dir: PathBuf,
+    pub logs_dir: PathBuf,
 }
 
 impl AppDirs {
-    fn new(log_file: Option<PathBuf>) -> Result<Self, io::Error> {
-        let data_dir = Self::get_data_dir()?;
+    // platform data directory is used if data_dir is not set
+    fn new(data_dir: Option<PathBuf>, log_file: Option<PathBuf>) -> Result<Self, io::Error> {
+        let data_dir = match data_dir {
+            Some(data_dir) => data_dir,
+            None => Self::get_data_dir()?,
+        };
 
         Ok(Self {
             log_file: log_file.unwrap_or_else(|| Self::default_log_path(&data_dir)),
             installations_dir: Self::get_installations_dir(&data_dir)?,
+            downloads_dir: Self::get_downloads_dir(&data_dir)?,
+            staging_dir: Self::get_staging_dir(&data_dir)?,
+            exports_dir: Self::get_exports_dir(&data_dir)?,
+            logs_dir: Self::get_logs_dir(&data_dir)?,
             data_dir,
         })
     }
@@ -81,6 +380,38 @@ impl AppDirs {
         Ok(installations_dir)
     }
 
+    fn get_downloads_dir(data_dir: &Path) -> Result<PathBuf, io::Error> {
+        let downloads_dir = data_dir.join("downloads");
+
+        fs::create_dir_all(&downloads_dir)?;
+
+        Ok(downloads_dir)
+    }
+
+    fn get_staging_dir(data_dir: &Path) -> Result<PathBuf, io::Error> {
+        let staging_dir = data_dir.join("staging");
+
+        fs::create_dir_all(&staging_dir)?;
+
+        Ok(staging_dir)
+    }
+
+    fn get_exports_dir(data_dir: &Path) -> Result<PathBuf, io::Error> {
+        let exports_dir = data_dir.join("exports");
+
+        fs::create_dir_all(&exports_dir)?;
+
+        Ok(exports_dir)
+    }
+
+    fn get_logs_dir(data_dir: &Path) -> Result<PathBuf, io::Error> {
+        let logs_dir = data_dir.join("logs");
+
+        fs::create_dir_all(&logs_dir)?;
+
+        Ok(logs_dir)
+    }
+
     fn default_log_path(data_dir: &Path) -> PathBuf {
         // TODO: rotate by count or date or something
         data_dir.join(format!("{}.log", env!("CARGO_PKG_NAME")))
@@ -95,13 +426,64 @@ pub struct AppConfig {
     pub geo_provider: reqwest::Url,
     pub offline: bool,
     pub unchecked_downloads: bool,
+    pub ping_concurrency: usize,
+    pub max_downloads: usize,
+    // bytes per second, 0 means unlimited
+    pub max_download_rate: u64,
+    // builds used by listed servers and pinned builds are always kept
+    pub keep_builds: usize,
+    pub auto_cleanup: bool,
+    pub confirm_destructive: bool,
+    pub export_format: ExportFormat,
+    // bytes, 0 means disabled
+    pub history_max_size: u64,
+    pub history_interval: u64,
+    pub watch: Vec<WatchRule>,
+    pub watch_players: u32,
+    pub watch_bell: bool,
+    pub watch_command: Option<String>,
+    pub server_list_url: reqwest::Url,
+    pub commits_url: reqwest::Url,
+    pub trusted_downloads: Vec<TrustedDownload>,
+    pub profiles: BTreeMap<String, LaunchProfile>,
+    // applied to versions without own or fork profile
+    pub default_profile: Option<String>,
+    pub fork_profiles: BTreeMap<String, String>,
+    // headless mode if set
+    pub command: Option<Command>,
+    // config is printed instead of running app
+    pub print_config: bool,
+    config_path: Option<PathBuf>,
 
     pub dirs: AppDirs,
 }
 
 impl AppConfig {
-    pub fn new() -> io::Result<Self> {
+    pub fn new() -> anyhow::Result<Self> {
+        Self::from_args(CliArgs::parse(), None)
+    }
+
+    // isolated from user config and files
+    #[cfg(test)]
+    pub fn for_tests(data_dir: &Path) -> Self {
+        let config_file = data_dir.join("config.toml");
+
+        fs::create_dir_all(data_dir).expect("creating test data directory");
+        fs::write(&config_file, "").expect("writing test config");
+
+        let args = CliArgs::parse_from([
+            env!("CARGO_PKG_NAME").as_ref(),
+            "--config".as_ref(),
+            config_file.as_os_str(),
+        ]);
+
+        Self::from_args(args, Some(data_dir.to_owned())).expect("creating test config")
+    }
+
+    fn from_args(args: CliArgs, data_dir: Option<PathBuf>) -> anyhow::Result<Self> {
         let CliArgs {
+            config,
+            print_config,
             log_file,
             update_interval,
             verbose,
@@ -109,17 +491,216 @@ impl AppConfig {
             geo_provider,
             offline,
             unchecked_downloads,
-        } = CliArgs::parse();
+            ping_concurrency,
+            max_downloads,
+            max_download_rate,
+            keep_builds,
+            no_confirm,
+            auto_cleanup,
+            export_format,
+            history_max_size,
+            history_interval,
+            watch_players,
+            watch_bell,
+            watch_command,
+            server_list_url,
+            commits_url,
+            trusted_downloads,
+            command,
+        } = args;
 
-        Ok(Self {
-            dirs: AppDirs::new(log_file)?,
+        let (config_path, file) = match config {
+            Some(path) => {
+                let file = ConfigFile::load(&path, true)?;
+                (Some(path), file)
+            }
+            None => match ConfigFile::default_path() {
+                Some(path) => {
+                    let file = ConfigFile::load(&path, false)?;
+                    (Some(path), file)
+                }
+                None => (None, ConfigFile::default()),
+            },
+        };
 
-            update_interval,
-            verbose,
+        #[cfg(feature = "geolocation")]
+        let geo_provider = match (geo_provider, &file.geo_provider) {
+            (Some(url), _) => url,
+            (None, Some(url)) => reqwest::Url::parse(url)
+                .with_context(|| format!("Invalid config file value geo_provider: {}", url))?,
+            (None, None) => {
+                reqwest::Url::parse(DEFAULT_GEO_PROVIDER_URL).expect("parsing default geo provider")
+            }
+        };
+
+        let max_download_rate = match (max_download_rate, &file.max_download_rate) {
+            (Some(rate), _) => rate.as_u64(),
+            (None, Some(rate)) => rate
+                .parse::<ByteSize>()
+                .map_err(|e| anyhow!("Invalid config file value max_download_rate: {}", e))?
+                .as_u64(),
+            (None, None) => 0,
+        };
+
+        let history_max_size = match (history_max_size, &file.history_max_size) {
+            (Some(size), _) => size.as_u64(),
+            (None, Some(size)) => size
+                .parse::<ByteSize>()
+                .map_err(|e| anyhow!("Invalid config file value history_max_size: {}", e))?
+                .as_u64(),
+            (None, None) => DEFAULT_HISTORY_MAX_SIZE,
+        };
+
+        let export_format = match (export_format, &file.export_format) {
+            (Some(format), _) => format,
+            (None, Some(format)) => format
+                .parse()
+                .map_err(|e| anyhow!("Invalid config file value export_format: {}", e))?,
+            (None, None) => ExportFormat::Json,
+        };
+
+        let server_list_url = match server_list_url {
+            Some(url) => url,
+            None => ConfigFile::parse_url(
+                "server_list_url",
+                &file.server_list_url,
+                DEFAULT_SERVER_LIST_URL,
+            )?,
+        };
+        let commits_url = match commits_url {
+            Some(url) => url,
+            None => ConfigFile::parse_url(
+                "commits_url",
+                &file.commits_url,
+                DEFAULT_GITHUB_REPO_COMMIT_ENDPOINT_URL,
+            )?,
+        };
+        let trusted_downloads = if !trusted_downloads.is_empty() {
+            trusted_downloads
+        } else {
+            file.trusted_downloads
+                .clone()
+                .unwrap_or_else(|| vec![DEFAULT_TRUSTED_DOWNLOAD.to_owned()])
+                .iter()
+                .map(|s| s.parse())
+                .collect::<Result<_, _>>()
+                .map_err(|e| anyhow!("Invalid config file value trusted_downloads: {}", e))?
+        };
+
+        // occurrence counter can not be read from environment by clap
+        let env_verbose = match env::var("RSHUB_VERBOSE") {
+            Ok(level) => Some(
+                level
+                    .parse::<u32>()
+                    .with_context(|| format!("Invalid RSHUB_VERBOSE value: {}", level))?,
+            ),
+            Err(env::VarError::NotPresent) => None,
+            Err(err) => bail!("Invalid RSHUB_VERBOSE value: {}", err),
+        };
+
+        let instance = Self {
+            dirs: AppDirs::new(data_dir, log_file.or(file.log_file))?,
+
+            update_interval: update_interval
+                .or(file.update_interval)
+                .unwrap_or(DEFAULT_UPDATE_INTERVAL),
+            verbose: if verbose != 0 {
+                verbose
+            } else {
+                env_verbose.or(file.verbose).unwrap_or_default()
+            },
             #[cfg(feature = "geolocation")]
             geo_provider,
-            offline,
-            unchecked_downloads,
-        })
+            offline: offline.or(file.offline).unwrap_or_default(),
+            unchecked_downloads: unchecked_downloads
+                .or(file.unchecked_downloads)
+                .unwrap_or_default(),
+            ping_concurrency: ping_concurrency
+                .or(file.ping_concurrency)
+                .unwrap_or(DEFAULT_PING_CONCURRENCY),
+            max_downloads: max_downloads
+                .or(file.max_downloads)
+                .unwrap_or(DEFAULT_MAX_DOWNLOADS),
+            max_download_rate,
+            keep_builds: keep_builds
+                .or(file.keep_builds)
+                .unwrap_or(DEFAULT_KEEP_BUILDS),
+            auto_cleanup: auto_cleanup.or(file.auto_cleanup).unwrap_or_default(),
+            confirm_destructive: no_confirm
+                .map(|no_confirm| !no_confirm)
+                .or(file.confirm_destructive)
+                .unwrap_or(true),
+            export_format,
+            history_max_size,
+            history_interval: history_interval
+                .or(file.history_interval)
+                .unwrap_or(DEFAULT_HISTORY_INTERVAL),
+            watch: file.watch.unwrap_or_default(),
+            watch_players: watch_players
+                .or(file.watch_players)
+                .unwrap_or(DEFAULT_WATCH_PLAYERS),
+            watch_bell: watch_bell.or(file.watch_bell).unwrap_or_default(),
+            watch_command: watch_command.or(file.watch_command),
+            server_list_url,
+            commits_url,
+            trusted_downloads,
+            profiles: file.profiles.unwrap_or_default(),
+            default_profile: file.default_profile,
+            fork_profiles: file.fork_profiles.unwrap_or_default(),
+            command,
+            print_config,
+            config_path,
+        };
+
+        Ok(instance)
+    }
+
+    pub fn print(&self) -> anyhow::Result<()> {
+        if let Some(path) = &self.config_path {
+            println!("# {}", path.display());
+        }
+        print!("{}", self.to_toml()?);
+
+        Ok(())
+    }
+
+    fn to_toml(&self) -> anyhow::Result<String> {
+        let file = ConfigFile {
+            log_file: Some(self.dirs.log_file.clone()),
+            update_interval: Some(self.update_interval),
+            verbose: Some(self.verbose),
+            #[cfg(feature = "geolocation")]
+            geo_provider: Some(self.geo_provider.to_string()),
+            #[cfg(not(feature = "geolocation"))]
+            geo_provider: None,
+            offline: Some(self.offline),
+            unchecked_downloads: Some(self.unchecked_downloads),
+            ping_concurrency: Some(self.ping_concurrency),
+            max_downloads: Some(self.max_downloads),
+            max_download_rate: Some(ByteSize::b(self.max_download_rate).to_string_as(true)),
+            keep_builds: Some(self.keep_builds),
+            auto_cleanup: Some(self.auto_cleanup),
+            confirm_destructive: Some(self.confirm_destructive),
+            export_format: Some(self.export_format.to_string()),
+            history_max_size: Some(ByteSize::b(self.history_max_size).to_string_as(true)),
+            history_interval: Some(self.history_interval),
+            watch_players: Some(self.watch_players),
+            watch_bell: Some(self.watch_bell),
+            watch_command: self.watch_command.clone(),
+            server_list_url: Some(self.server_list_url.to_string()),
+            commits_url: Some(self.commits_url.to_string()),
+            trusted_downloads: Some(
+                self.trusted_downloads
+                    .iter()
+                    .map(TrustedDownload::to_string)
+                    .collect(),
+            ),
+            default_profile: self.default_profile.clone(),
+            fork_profiles: Some(self.fork_profiles.clone()),
+            profiles: Some(self.profiles.clone()),
+            watch: Some(self.watch.clone()),
+        };
+
+        toml::to_string(&file).with_context(|| "Unable to serialize config")
     }
 }
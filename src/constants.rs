@@ -4,8 +4,8 @@ pub const USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), " ", env!("CARGO_PK
 
 #[cfg(feature = "geolocation")]
 pub const DEFAULT_GEO_PROVIDER_URL: &str = "https://ifconfig.based.computer";
-pub const DEFAULT_CDN_DOMAIN: &str = "unitystationfile.b-cdn.net";
+pub const DEFAULT_TRUSTED_DOWNLOAD: &str = "https://unitystationfile.b-cdn.net";
 
-pub const SERVER_LIST_URL: &str = "https://api.unitystation.org/serverlist";
-pub const GITHUB_REPO_COMMIT_ENDPOINT_URL: &str =
+pub const DEFAULT_SERVER_LIST_URL: &str = "https://api.unitystation.org/serverlist";
+pub const DEFAULT_GITHUB_REPO_COMMIT_ENDPOINT_URL: &str =
     "https://api.github.com/repos/unitystation/unitystation/commits";
@@ -24,6 +24,7 @@ pub struct GitHubJunkCommitJson {
 pub struct CommitsJson(pub Vec<GitHubJunkCommitJson>);
 
 #[derive(Debug)]
+#[allow(dead_code)]
 pub struct Commit {
     pub sha: String,
     pub title: String,
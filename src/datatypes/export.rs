@@ -0,0 +1,98 @@
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::Context;
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Csv,
+    // one json object per line
+    Ndjson,
+}
+
+impl ExportFormat {
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+            Self::Ndjson => "ndjson",
+        }
+    }
+
+    pub fn export(self, records: &[ServerRecord]) -> anyhow::Result<String> {
+        match self {
+            Self::Json => {
+                serde_json::to_string_pretty(records).with_context(|| "Unable to serialize JSON")
+            }
+            Self::Ndjson => {
+                let mut result = String::new();
+
+                for record in records {
+                    result.push_str(
+                        &serde_json::to_string(record)
+                            .with_context(|| "Unable to serialize JSON")?,
+                    );
+                    result.push('\n');
+                }
+
+                Ok(result)
+            }
+            Self::Csv => {
+                let mut writer = csv::Writer::from_writer(Vec::new());
+
+                for record in records {
+                    writer
+                        .serialize(record)
+                        .with_context(|| "Unable to serialize CSV")?;
+                }
+
+                String::from_utf8(writer.into_inner()?).with_context(|| "Invalid CSV output")
+            }
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            "ndjson" => Ok(Self::Ndjson),
+            _ => Err(format!(
+                "unknown format {}, expected json, csv or ndjson",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.extension())
+    }
+}
+
+// flat server description including derived fields. flat to be representable in csv
+#[derive(Debug, Serialize)]
+pub struct ServerRecord {
+    pub name: String,
+    pub address: String,
+    pub players: u32,
+    pub fork: String,
+    pub build: String,
+    pub map: String,
+    pub gamemode: String,
+    pub time: String,
+    pub fps: u32,
+    pub ping_ms: Option<u64>,
+    pub offline: bool,
+    // country/city, only resolved with geolocation feature
+    pub location: Option<String>,
+    // version is installed locally
+    pub installed: bool,
+}
@@ -0,0 +1,194 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use sha2::{Digest, Sha256};
+
+use tokio::fs;
+
+use crate::datatypes::installation::{ARCHIVE_CHECKSUM_FILE, MANIFEST_FILE};
+
+// sha256 of every extracted file in sha256sum compatible format, written to build directory
+pub const FILE_LIST_FILE: &str = "files.sha256";
+
+// written by rshub after extraction, not part of build
+const METADATA_FILES: [&str; 3] = [ARCHIVE_CHECKSUM_FILE, MANIFEST_FILE, FILE_LIST_FILE];
+
+// differences are listed up to this count, rest is only counted
+const MAX_LISTED: usize = 20;
+
+#[derive(Debug, Default)]
+pub struct FileList {
+    // relative path with / separators to sha256
+    files: BTreeMap<String, String>,
+}
+
+impl FileList {
+    // blocking, reads every file
+    pub fn hash_dir(dir: &Path) -> Result<Self> {
+        let mut files = BTreeMap::new();
+
+        let mut dirs_to_check = vec![dir.to_owned()];
+
+        while let Some(next_dir) = dirs_to_check.pop() {
+            for entry in std::fs::read_dir(&next_dir)
+                .with_context(|| format!("Unable to read {}", next_dir.display()))?
+            {
+                let path = entry?.path();
+
+                if path.is_dir() {
+                    dirs_to_check.push(path);
+                    continue;
+                }
+
+                let relative = path
+                    .strip_prefix(dir)?
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+
+                if METADATA_FILES.contains(&relative.as_str()) {
+                    continue;
+                }
+
+                let mut hasher = Sha256::new();
+
+                io::copy(
+                    &mut std::fs::File::open(&path)
+                        .with_context(|| format!("Unable to open {}", path.display()))?,
+                    &mut hasher,
+                )
+                .with_context(|| format!("Unable to read {}", path.display()))?;
+
+                files.insert(relative, format!("{:x}", hasher.finalize()));
+            }
+        }
+
+        Ok(Self { files })
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    // None for installations made before file lists existed
+    pub async fn load(dir: &Path) -> Result<Option<Self>> {
+        let path = dir.join(FILE_LIST_FILE);
+
+        let data = match fs::read_to_string(&path).await {
+            Ok(data) => data,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(err).with_context(|| format!("Unable to read {}", path.display()))
+            }
+        };
+
+        let files = data
+            .lines()
+            .map(|line| {
+                line.split_once("  ")
+                    .map(|(hash, file)| (file.to_owned(), hash.to_owned()))
+                    .with_context(|| format!("Bad line in {}: {}", path.display(), line))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Some(Self { files }))
+    }
+
+    pub async fn save(&self, dir: &Path) -> Result<()> {
+        let mut data = String::new();
+
+        for (file, hash) in &self.files {
+            data.push_str(&format!("{}  {}\n", hash, file));
+        }
+
+        fs::write(dir.join(FILE_LIST_FILE), data)
+            .await
+            .with_context(|| "Unable to save file list")
+    }
+
+    // compares recorded list with current state of directory
+    pub fn compare(&self, current: &Self) -> Verification {
+        let mut verification = Verification {
+            checked: self.files.len(),
+            ..Verification::default()
+        };
+
+        for (file, hash) in &self.files {
+            match current.files.get(file) {
+                None => verification.missing.push(file.clone()),
+                Some(current_hash) if current_hash != hash => {
+                    verification.modified.push(file.clone());
+                }
+                Some(_) => {}
+            }
+        }
+
+        verification.extra = current
+            .files
+            .keys()
+            .filter(|file| !self.files.contains_key(*file))
+            .cloned()
+            .collect();
+
+        verification
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Verification {
+    pub checked: usize,
+    pub missing: Vec<String>,
+    pub modified: Vec<String>,
+    // files created after installation, for example by game itself
+    pub extra: Vec<String>,
+}
+
+impl Verification {
+    // extra files do not break build
+    pub fn is_intact(&self) -> bool {
+        self.missing.is_empty() && self.modified.is_empty()
+    }
+
+    // one line per file
+    pub fn details(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        for (kind, files) in [
+            ("missing", &self.missing),
+            ("modified", &self.modified),
+            ("extra", &self.extra),
+        ] {
+            for file in files.iter().take(MAX_LISTED) {
+                lines.push(format!("{:<8} {}", kind, file));
+            }
+
+            if files.len() > MAX_LISTED {
+                lines.push(format!("{:<8} {} more", kind, files.len() - MAX_LISTED));
+            }
+        }
+
+        lines
+    }
+}
+
+impl fmt::Display for Verification {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_intact() && self.extra.is_empty() {
+            return write!(f, "all {} files are intact", self.checked);
+        }
+
+        write!(
+            f,
+            "{} missing, {} modified, {} extra of {} files",
+            self.missing.len(),
+            self.modified.len(),
+            self.extra.len(),
+            self.checked
+        )
+    }
+}
@@ -1,10 +1,54 @@
 use std::cmp::Ordering;
 use std::fmt;
 use std::path::PathBuf;
+use std::str::FromStr;
 
-use crate::constants::DEFAULT_CDN_DOMAIN;
 use crate::datatypes::server::ServerJson;
 
+// scheme, host and port downloads are allowed from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrustedDownload {
+    url: reqwest::Url,
+}
+
+impl TrustedDownload {
+    // origin compares port_or_known_default, https://host and https://host:443 are same
+    pub fn matches(&self, url: &reqwest::Url) -> bool {
+        url.origin() == self.url.origin()
+    }
+}
+
+impl FromStr for TrustedDownload {
+    type Err = String;
+
+    // accepts either scheme://host[:port] or host[:port], https is assumed in second case
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let with_scheme = if s.contains("://") {
+            s.to_owned()
+        } else {
+            format!("https://{}", s)
+        };
+
+        let url = reqwest::Url::parse(&with_scheme).map_err(|e| format!("{}: {}", s, e))?;
+
+        if url.path() != "/" || url.query().is_some() {
+            return Err(format!("{}: only scheme and host are allowed", s));
+        }
+
+        if url.host_str().is_none() {
+            return Err(format!("{}: missing host", s));
+        }
+
+        Ok(Self { url })
+    }
+}
+
+impl fmt::Display for TrustedDownload {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.url.origin().ascii_serialization())
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum DownloadUrl {
     Valid(reqwest::Url),
@@ -14,16 +58,14 @@ pub enum DownloadUrl {
 }
 
 impl DownloadUrl {
-    pub fn new(url: &str) -> Self {
+    pub fn new(url: &str, trusted: &[TrustedDownload]) -> Self {
         match reqwest::Url::parse(url) {
             Ok(parsed) => {
                 // https://github.com/unitystation/stationhub/blob/cebb9d45bff0a1c019852795a471068ba89d770a/UnitystationLauncher/Models/Server.cs#L37-L57
-                if parsed.scheme() != "https"
-                    || parsed.host().map(|h| h.to_string()) != Some(DEFAULT_CDN_DOMAIN.to_owned())
-                {
-                    Self::Untrusted(parsed)
-                } else {
+                if trusted.iter().any(|t| t.matches(&parsed)) {
                     Self::Valid(parsed)
+                } else {
+                    Self::Untrusted(parsed)
                 }
             }
             Err(e) => {
@@ -80,8 +122,8 @@ pub struct GameVersion {
     pub download: DownloadUrl,
 }
 
-impl From<ServerJson> for GameVersion {
-    fn from(data: ServerJson) -> Self {
+impl GameVersion {
+    pub fn new(data: ServerJson, trusted: &[TrustedDownload]) -> Self {
         let ServerJson {
             fork,
             build,
@@ -93,7 +135,7 @@ impl From<ServerJson> for GameVersion {
             // replace / for security reasons, just in case
             fork: fork.replace('/', ""),
             build: build.to_string(),
-            download: DownloadUrl::new(&download),
+            download: DownloadUrl::new(&download, trusted),
         }
     }
 }
@@ -138,3 +180,25 @@ impl From<GameVersion> for PathBuf {
         PathBuf::from(version.fork).join(version.build)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trusted_download_port() {
+        let url = |s: &str| reqwest::Url::parse(s).unwrap();
+
+        let trusted: TrustedDownload = "cdn.example.org".parse().unwrap();
+        assert!(trusted.matches(&url("https://cdn.example.org/build.zip")));
+        assert!(trusted.matches(&url("https://cdn.example.org:443/build.zip")));
+        assert!(!trusted.matches(&url("https://cdn.example.org:8443/build.zip")));
+        assert!(!trusted.matches(&url("http://cdn.example.org/build.zip")));
+        assert_eq!(trusted.to_string(), "https://cdn.example.org");
+
+        let trusted: TrustedDownload = "http://127.0.0.1:8931".parse().unwrap();
+        assert!(trusted.matches(&url("http://127.0.0.1:8931/build.zip")));
+        assert!(!trusted.matches(&url("http://127.0.0.1/build.zip")));
+        assert_eq!(trusted.to_string(), "http://127.0.0.1:8931");
+    }
+}
@@ -1,3 +1,4 @@
+#[cfg(feature = "geolocation")]
 use std::convert::TryFrom;
 use std::fmt;
 
@@ -27,6 +28,7 @@ pub struct LocationJson {
     pub city: String,
 }
 
+#[cfg(feature = "geolocation")]
 #[derive(Debug)]
 pub struct Location {
     pub longitude: f64,
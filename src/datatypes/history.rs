@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+
+use chrono::{DateTime, Duration, Utc};
+
+use serde::{Deserialize, Serialize};
+
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+
+use crate::datatypes::server::Server;
+
+// one line of history file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sample {
+    pub time: DateTime<Utc>,
+    pub address: String,
+    pub players: u32,
+    pub map: String,
+    pub fps: u32,
+    pub online: bool,
+}
+
+// population samples stored as ndjson. when file grows past max_size, oldest samples are
+// removed until it is 3/4 of max_size
+pub struct History {
+    path: PathBuf,
+    max_size: u64,
+    interval: Duration,
+    last_sample: Option<DateTime<Utc>>,
+    samples: HashMap<String, Vec<Sample>>,
+}
+
+impl History {
+    pub async fn load(path: &Path, max_size: u64, interval: Duration) -> Self {
+        let mut instance = Self {
+            path: path.to_owned(),
+            max_size,
+            interval,
+            last_sample: None,
+            samples: HashMap::new(),
+        };
+
+        if !instance.enabled() {
+            return instance;
+        }
+
+        let data = match fs::read_to_string(path).await {
+            Ok(data) => data,
+            Err(err) => {
+                log::debug!("unable to read {}: {}", path.display(), err);
+                return instance;
+            }
+        };
+
+        for line in data.lines() {
+            match serde_json::from_str::<Sample>(line) {
+                Ok(sample) => instance.insert(sample),
+                Err(err) => log::warn!("bad history line in {}: {}", path.display(), err),
+            }
+        }
+
+        instance
+    }
+
+    pub const fn enabled(&self) -> bool {
+        self.max_size != 0
+    }
+
+    fn insert(&mut self, sample: Sample) {
+        if self.last_sample.map_or(true, |last| last < sample.time) {
+            self.last_sample = Some(sample.time);
+        }
+
+        self.samples
+            .entry(sample.address.clone())
+            .or_default()
+            .push(sample);
+    }
+
+    // appends sample of every server if interval passed since previous one
+    pub async fn record(&mut self, servers: &[Server]) -> anyhow::Result<()> {
+        let now = Utc::now();
+
+        if !self.enabled()
+            || self
+                .last_sample
+                .map_or(false, |last| now - last < self.interval)
+        {
+            return Ok(());
+        }
+
+        let mut lines = String::new();
+
+        for server in servers {
+            let sample = Sample {
+                time: now,
+                address: server.address.to_string(),
+                players: server.players,
+                map: server.map.clone(),
+                fps: server.fps,
+                online: !server.offline,
+            };
+
+            lines.push_str(&serde_json::to_string(&sample)?);
+            lines.push('\n');
+
+            self.insert(sample);
+        }
+
+        // updated even if there are no servers to not retry on every update
+        self.last_sample = Some(now);
+
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .with_context(|| format!("Unable to open {}", self.path.display()))?;
+
+        file.write_all(lines.as_bytes())
+            .await
+            .with_context(|| format!("Unable to write {}", self.path.display()))?;
+
+        if file.metadata().await?.len() > self.max_size {
+            drop(file);
+
+            self.trim().await?;
+        }
+
+        Ok(())
+    }
+
+    async fn trim(&mut self) -> anyhow::Result<()> {
+        let data = fs::read_to_string(&self.path).await?;
+
+        let target = (self.max_size / 4 * 3) as usize;
+        let mut start = data.len().saturating_sub(target);
+
+        // cut at line boundary. searched in bytes because start can be inside character
+        if start != 0 {
+            start = match data.as_bytes()[start..].iter().position(|&b| b == b'\n') {
+                Some(newline) => start + newline + 1,
+                None => data.len(),
+            };
+        }
+
+        let kept = &data[start..];
+
+        let oldest = kept
+            .lines()
+            .next()
+            .and_then(|line| serde_json::from_str::<Sample>(line).ok())
+            .map(|sample| sample.time);
+
+        log::info!(
+            "trimming history {}: {} -> {} bytes",
+            self.path.display(),
+            data.len(),
+            kept.len()
+        );
+
+        let tmp = self.path.with_extension("ndjson.tmp");
+
+        fs::write(&tmp, kept).await?;
+        fs::rename(&tmp, &self.path)
+            .await
+            .with_context(|| format!("Unable to replace {}", self.path.display()))?;
+
+        for samples in self.samples.values_mut() {
+            samples.retain(|s| oldest.map_or(false, |oldest| s.time >= oldest));
+        }
+        self.samples.retain(|_, samples| !samples.is_empty());
+
+        Ok(())
+    }
+
+    // max player count per bucket for last period, oldest first
+    pub fn players(&self, address: &str, period: Duration, buckets: usize) -> Vec<u64> {
+        let mut result = vec![0; buckets];
+
+        if buckets == 0 {
+            return result;
+        }
+
+        let now = Utc::now();
+        let start = now - period;
+        let bucket_length = period / buckets as i32;
+
+        for sample in self.samples.get(address).into_iter().flatten() {
+            if sample.time < start || !sample.online {
+                continue;
+            }
+
+            let i =
+                ((sample.time - start).num_seconds() / bucket_length.num_seconds().max(1)) as usize;
+
+            if let Some(bucket) = result.get_mut(i.min(buckets - 1)) {
+                *bucket = (*bucket).max(sample.players as u64);
+            }
+        }
+
+        result
+    }
+
+    // peak and average player count for last period
+    pub fn stats(&self, address: &str, period: Duration) -> Option<(u32, f64)> {
+        let start = Utc::now() - period;
+
+        let players: Vec<u32> = self
+            .samples
+            .get(address)?
+            .iter()
+            .filter(|s| s.time >= start && s.online)
+            .map(|s| s.players)
+            .collect();
+
+        let peak = *players.iter().max()?;
+        let average = players.iter().map(|&p| p as f64).sum::<f64>() / players.len() as f64;
+
+        Some((peak, average))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn trim_non_ascii() {
+        let path =
+            std::env::temp_dir().join(format!("rshub-history-{}.ndjson", std::process::id()));
+
+        let lines: String = (0..20)
+            .map(|i| {
+                let sample = Sample {
+                    time: Utc::now() + Duration::seconds(i),
+                    address: format!("127.0.0.1:{}", 7777 + i),
+                    players: i as u32,
+                    map: "Станция Ωмега 🚀".to_owned(),
+                    fps: 60,
+                    online: true,
+                };
+
+                serde_json::to_string(&sample).unwrap() + "\n"
+            })
+            .collect();
+
+        // every cut offset, most of them are inside multi byte characters
+        for max_size in 100..lines.len() as u64 {
+            fs::write(&path, &lines).await.unwrap();
+
+            let mut history = History::load(&path, max_size, Duration::minutes(1)).await;
+            history.trim().await.unwrap();
+
+            let kept = fs::read_to_string(&path).await.unwrap();
+
+            assert!(kept.len() as u64 <= max_size / 4 * 3);
+            assert!(lines.ends_with(&kept));
+            assert!(kept.is_empty() || kept.ends_with('\n'));
+            for line in kept.lines() {
+                serde_json::from_str::<Sample>(line).unwrap();
+            }
+        }
+
+        fs::remove_file(&path).await.unwrap();
+    }
+}
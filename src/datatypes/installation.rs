@@ -1,19 +1,54 @@
 use std::cmp::Ordering;
 use std::io;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
+
+use chrono::{DateTime, Utc};
+
+use serde::{Deserialize, Serialize};
 
 use tokio::fs;
 
 use crate::datatypes::game_version::{DownloadUrl, GameVersion};
 
+// sha256 of downloaded archive, written to build directory
+pub const ARCHIVE_CHECKSUM_FILE: &str = "archive.sha256";
+// installation metadata, written to build directory
+pub const MANIFEST_FILE: &str = "manifest.json";
+
 #[derive(Debug, Clone)]
 pub enum InstallationKind {
     Discovered,
-    Installed { size: u64 },
-    Downloading { progress: u64, total: Option<u64> },
+    Queued {
+        paused: bool,
+    },
+    Installed {
+        size: u64,
+        // None for legacy installations made before manifests existed
+        manifest: Option<Manifest>,
+    },
+    Downloading {
+        progress: u64,
+        total: Option<u64>,
+        // bytes per second, smoothed
+        speed: Option<u64>,
+    },
     Unpacking,
+    // extraction or validation failed, installation directory might be left
+    Broken {
+        reason: String,
+    },
+}
+
+impl InstallationKind {
+    // queued, downloading or unpacking
+    pub const fn is_pending(&self) -> bool {
+        matches!(
+            self,
+            Self::Queued { .. } | Self::Downloading { .. } | Self::Unpacking
+        )
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -23,6 +58,152 @@ pub struct Installation {
 }
 
 impl Installation {
+    // https://github.com/unitystation/stationhub/blob/cebb9d45bff0a1c019852795a471068ba89d770a/UnitystationLauncher/Models/Installation.cs#L33-L104
+    pub fn executable_path(dir: &Path) -> Result<PathBuf> {
+        #[cfg(target_family = "unix")]
+        return Ok(dir.join("Unitystation"));
+        #[cfg(target_os = "windows")]
+        return Ok(dir.join("Unitystation.exe"));
+        #[cfg(not(any(target_family = "unix", target_os = "windows")))]
+        anyhow::bail!("Unsupported OS");
+    }
+
+    fn executable_metadata(dir: &Path) -> Result<(PathBuf, std::fs::Metadata)> {
+        let executable = Self::executable_path(dir)?;
+        let name = executable.file_name().unwrap_or_default().to_string_lossy();
+
+        let metadata = match std::fs::metadata(&executable) {
+            Ok(metadata) => metadata,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                bail!("executable {} not found", name)
+            }
+            Err(err) => bail!("unable to access executable {}: {}", name, err),
+        };
+
+        if !metadata.is_file() {
+            bail!("executable {} is not a file", name);
+        }
+
+        Ok((executable, metadata))
+    }
+
+    // checks that build can be launched, nothing is changed on disk
+    pub fn check_executable(dir: &Path) -> Result<PathBuf> {
+        #[cfg_attr(not(unix), allow(unused_variables))]
+        let (executable, metadata) = Self::executable_metadata(dir)?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            if metadata.permissions().mode() & 0o111 == 0 {
+                bail!(
+                    "executable {} has no execute permission",
+                    executable.file_name().unwrap_or_default().to_string_lossy()
+                );
+            }
+        }
+
+        Ok(executable)
+    }
+
+    // same as check_executable, but executable is made runnable if archive had no permissions
+    // stored. only used after extraction and on repair
+    pub fn make_executable(dir: &Path) -> Result<PathBuf> {
+        #[cfg_attr(not(unix), allow(unused_variables))]
+        let (executable, metadata) = Self::executable_metadata(dir)?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let mut permissions = metadata.permissions();
+
+            if permissions.mode() & 0o111 == 0 {
+                log::info!("making {} executable", executable.display());
+
+                permissions.set_mode(permissions.mode() | 0o755);
+                std::fs::set_permissions(&executable, permissions).with_context(|| {
+                    format!("Unable to make {} executable", executable.display())
+                })?;
+            }
+        }
+
+        Ok(executable)
+    }
+
+    // blocking. unlike ZipArchive::extract, directory permissions are applied after all files
+    // are written, otherwise read only directories can not be filled
+    pub fn extract(archive: &Path, dest: &Path) -> Result<()> {
+        let mut archive = zip::read::ZipArchive::new(
+            std::fs::File::open(archive).with_context(|| "Unable to read zip file")?,
+        )
+        .with_context(|| "Unable to decode zip file")?;
+
+        #[cfg_attr(not(unix), allow(unused_mut, unused_variables))]
+        let mut dir_modes = Vec::new();
+
+        for i in 0..archive.len() {
+            let mut entry = archive
+                .by_index(i)
+                .with_context(|| format!("Unable to read archive entry {}", i))?;
+
+            let path = dest.join(
+                entry
+                    .enclosed_name()
+                    .ok_or_else(|| anyhow!("Unsafe path in archive: {}", entry.name()))?,
+            );
+
+            if entry.is_dir() {
+                std::fs::create_dir_all(&path)
+                    .with_context(|| format!("Unable to create {}", path.display()))?;
+
+                if let Some(mode) = entry.unix_mode() {
+                    dir_modes.push((path, mode));
+                }
+
+                continue;
+            }
+
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)
+                    .with_context(|| format!("Unable to create {}", parent.display()))?;
+            }
+
+            let mut file = std::fs::File::create(&path)
+                .with_context(|| format!("Unable to create {}", path.display()))?;
+
+            io::copy(&mut entry, &mut file)
+                .with_context(|| format!("Unable to extract {}", entry.name()))?;
+
+            #[cfg(unix)]
+            if let Some(mode) = entry.unix_mode() {
+                use std::os::unix::fs::PermissionsExt;
+
+                std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode))
+                    .with_context(|| format!("Unable to set permissions of {}", path.display()))?;
+            }
+        }
+
+        #[cfg(unix)]
+        for (path, mode) in dir_modes {
+            use std::os::unix::fs::PermissionsExt;
+
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode))
+                .with_context(|| format!("Unable to set permissions of {}", path.display()))?;
+        }
+
+        Ok(())
+    }
+
+    // build is extracted here and moved to installations after validation. every process
+    // has own directory named after pid, so leftovers of running processes can be told apart
+    pub fn staging_path(staging_dir: &Path, version: &GameVersion) -> PathBuf {
+        staging_dir
+            .join(std::process::id().to_string())
+            .join(PathBuf::from(version.clone()))
+    }
+
     pub async fn try_from_dir(dir: &Path) -> Result<Self> {
         let build = dir
             .file_name()
@@ -39,16 +220,28 @@ impl Installation {
             .with_context(|| "Bad fork directory name")?
             .to_owned();
 
-        return Ok(Self {
+        let kind = match Self::check_executable(dir) {
+            Ok(_) => InstallationKind::Installed {
+                size: Self::get_folder_size(dir).await.unwrap_or_default(),
+                manifest: Manifest::load(dir).await,
+            },
+            Err(err) => {
+                log::warn!("broken installation {}: {:#}", dir.display(), err);
+
+                InstallationKind::Broken {
+                    reason: format!("{:#}", err),
+                }
+            }
+        };
+
+        Ok(Self {
             version: GameVersion {
                 fork,
                 build,
                 download: DownloadUrl::Local,
             },
-            kind: InstallationKind::Installed {
-                size: Self::get_folder_size(dir).await.unwrap_or_default(),
-            },
-        });
+            kind,
+        })
     }
 
     // not recursive because async recursion is not possible without hacks
@@ -103,3 +296,103 @@ impl Ord for Installation {
         }
     }
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub url: String,
+    pub installed_at: DateTime<Utc>,
+    pub sha256: String,
+    // name of server version was discovered on
+    pub server: Option<String>,
+    pub last_launched: Option<DateTime<Utc>>,
+}
+
+impl Manifest {
+    pub async fn load(dir: &Path) -> Option<Self> {
+        let path = dir.join(MANIFEST_FILE);
+        let data = fs::read(&path).await.ok()?;
+
+        match serde_json::from_slice(&data) {
+            Ok(manifest) => Some(manifest),
+            Err(err) => {
+                log::warn!("bad installation manifest {}: {}", path.display(), err);
+                None
+            }
+        }
+    }
+
+    pub async fn save(&self, dir: &Path) -> Result<()> {
+        fs::write(dir.join(MANIFEST_FILE), serde_json::to_vec_pretty(self)?)
+            .await
+            .with_context(|| "Unable to save installation manifest")
+    }
+}
+
+// build excluded from cleanup
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PinnedBuild {
+    pub fork: String,
+    pub build: String,
+}
+
+impl PinnedBuild {
+    pub fn matches(&self, version: &GameVersion) -> bool {
+        self.fork == version.fork && self.build == version.build
+    }
+}
+
+impl From<&GameVersion> for PinnedBuild {
+    fn from(version: &GameVersion) -> Self {
+        Self {
+            fork: version.fork.clone(),
+            build: version.build.clone(),
+        }
+    }
+}
+
+// state of interrupted download, stored next to partially downloaded archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartialDownload {
+    pub url: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub written: u64,
+}
+
+impl PartialDownload {
+    // fork directory, same layout as installations
+    pub fn archive_path(downloads_dir: &Path, version: &GameVersion) -> PathBuf {
+        downloads_dir
+            .join(&version.fork)
+            .join(format!("{}.zip.part", version.build))
+    }
+
+    pub fn metadata_path(downloads_dir: &Path, version: &GameVersion) -> PathBuf {
+        downloads_dir
+            .join(&version.fork)
+            .join(format!("{}.zip.part.json", version.build))
+    }
+
+    pub async fn load(path: &Path) -> Option<Self> {
+        let data = fs::read(path).await.ok()?;
+
+        match serde_json::from_slice(&data) {
+            Ok(partial) => Some(partial),
+            Err(err) => {
+                log::warn!("bad partial download metadata {}: {}", path.display(), err);
+                None
+            }
+        }
+    }
+
+    pub async fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_vec(self)?)
+            .await
+            .with_context(|| "Unable to save partial download metadata")
+    }
+
+    // resuming without validator could mix 2 different files
+    pub fn validator(&self) -> Option<&str> {
+        self.etag.as_deref().or(self.last_modified.as_deref())
+    }
+}
@@ -0,0 +1,74 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use tokio::process::Command;
+
+use crate::datatypes::game_version::GameVersion;
+
+// how game is started. only one profile is applied: selected for build, then profile
+// of fork, then default profile
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LaunchProfile {
+    // program executable is passed to, with its arguments. for example ["prime-run"]
+    pub wrapper: Vec<String>,
+    // passed to game after server arguments
+    pub args: Vec<String>,
+    // installation directory if not set
+    pub working_dir: Option<PathBuf>,
+    // table, must be after values to be serialized
+    pub env: BTreeMap<String, String>,
+}
+
+impl LaunchProfile {
+    pub fn command(&self, executable: &Path, installation_dir: &Path) -> Command {
+        let mut command = match self.wrapper.split_first() {
+            Some((program, args)) => {
+                let mut command = Command::new(program);
+                command.args(args).arg(executable);
+                command
+            }
+            None => Command::new(executable),
+        };
+
+        command
+            .current_dir(self.working_dir.as_deref().unwrap_or(installation_dir))
+            .envs(&self.env);
+
+        command
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileSource {
+    Build,
+    Fork,
+    Default,
+}
+
+impl fmt::Display for ProfileSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Build => write!(f, "build"),
+            Self::Fork => write!(f, "fork"),
+            Self::Default => write!(f, "default"),
+        }
+    }
+}
+
+// profile selected for build in versions tab
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildProfile {
+    pub fork: String,
+    pub build: String,
+    pub profile: String,
+}
+
+impl BuildProfile {
+    pub fn matches(&self, version: &GameVersion) -> bool {
+        self.fork == version.fork && self.build == version.build
+    }
+}
@@ -1,6 +1,12 @@
 pub mod commit;
+pub mod export;
+pub mod file_list;
 pub mod game_version;
 pub mod geolocation;
+pub mod history;
 pub mod installation;
+pub mod launch_profile;
+pub mod rate_limiter;
 pub mod server;
 pub mod value_sorted_map;
+pub mod watch;
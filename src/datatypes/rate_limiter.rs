@@ -0,0 +1,116 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// steps for runtime adjustment, in bytes per second. 0 means unlimited
+const RATE_STEPS: &[u64] = &[
+    0,
+    128 * 1024,
+    256 * 1024,
+    512 * 1024,
+    1024 * 1024,
+    2 * 1024 * 1024,
+    5 * 1024 * 1024,
+    10 * 1024 * 1024,
+    20 * 1024 * 1024,
+    50 * 1024 * 1024,
+];
+
+struct Bucket {
+    // bytes per second, 0 means unlimited
+    rate: u64,
+    // can go below 0: consumers wait until debt is paid off
+    tokens: f64,
+    refilled: Instant,
+}
+
+// token bucket shared between all downloads. bucket capacity is 1 second worth of tokens
+pub struct RateLimiter {
+    bucket: Mutex<Bucket>,
+}
+
+impl RateLimiter {
+    pub fn new(rate: u64) -> Self {
+        Self {
+            bucket: Mutex::new(Bucket {
+                rate,
+                tokens: rate as f64,
+                refilled: Instant::now(),
+            }),
+        }
+    }
+
+    pub fn rate(&self) -> u64 {
+        self.bucket.lock().unwrap().rate
+    }
+
+    pub fn set_rate(&self, rate: u64) {
+        let mut bucket = self.bucket.lock().unwrap();
+
+        Self::refill(&mut bucket);
+
+        bucket.rate = rate;
+        // drop debt accumulated under old rate
+        bucket.tokens = bucket.tokens.clamp(0.0, rate as f64);
+    }
+
+    // next preset step above or below current rate. unlimited is above every limit
+    pub fn step(&self, increase: bool) -> u64 {
+        let current = self.rate();
+
+        let rate = if increase {
+            if current == 0 {
+                0
+            } else {
+                RATE_STEPS
+                    .iter()
+                    .copied()
+                    .find(|&r| r > current)
+                    .unwrap_or(0)
+            }
+        } else if current == 0 {
+            RATE_STEPS[RATE_STEPS.len() - 1]
+        } else {
+            RATE_STEPS
+                .iter()
+                .copied()
+                .rev()
+                .find(|&r| r != 0 && r < current)
+                .unwrap_or(current)
+        };
+
+        self.set_rate(rate);
+
+        rate
+    }
+
+    // takes amount tokens, waiting if there are not enough of them
+    pub async fn acquire(&self, amount: u64) {
+        let wait = {
+            let mut bucket = self.bucket.lock().unwrap();
+
+            if bucket.rate == 0 {
+                return;
+            }
+
+            Self::refill(&mut bucket);
+
+            bucket.tokens -= amount as f64;
+
+            if bucket.tokens >= 0.0 {
+                return;
+            }
+
+            Duration::from_secs_f64(-bucket.tokens / bucket.rate as f64)
+        };
+
+        tokio::time::sleep(wait).await;
+    }
+
+    fn refill(bucket: &mut Bucket) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.refilled).as_secs_f64();
+
+        bucket.tokens = (bucket.tokens + elapsed * bucket.rate as f64).min(bucket.rate as f64);
+        bucket.refilled = now;
+    }
+}
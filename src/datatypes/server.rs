@@ -1,9 +1,12 @@
+use std::cmp::Ordering;
 use std::fmt;
+use std::net::IpAddr;
+use std::time::Duration;
 
-use serde::{Deserialize, Deserializer};
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
 
-use crate::datatypes::game_version::GameVersion;
+use crate::datatypes::game_version::{DownloadUrl, GameVersion, TrustedDownload};
 
 use crate::datatypes::geolocation::IP;
 
@@ -120,6 +123,7 @@ pub struct Server {
     pub gamemode: String,
     pub time: String,
     pub fps: u32,
+    pub ping: Option<Ping>,
     // ui update skip optimization
     // pub updated: bool,
     pub offline: bool,
@@ -144,6 +148,7 @@ impl Server {
             time,
             players,
             fps,
+            ping: None,
             version,
             address,
             // updated: true,
@@ -172,4 +177,406 @@ impl Server {
         self.players = *players;
         self.fps = *fps;
     }
+
+    pub fn matches(&self, pattern: &str) -> bool {
+        [&self.name, &self.map, &self.gamemode, &self.version.fork]
+            .iter()
+            .any(|field| fuzzy_match(field, pattern).is_some())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Ping {
+    pub latest: Duration,
+    // exponential moving average to avoid jumping around on single slow response
+    pub smoothed: Duration,
+}
+
+impl Ping {
+    pub fn new(previous: Option<Self>, rtt: Duration) -> Self {
+        let smoothed = match previous {
+            Some(previous) => previous.smoothed.mul_f64(0.7) + rtt.mul_f64(0.3),
+            None => rtt,
+        };
+
+        Self {
+            latest: rtt,
+            smoothed,
+        }
+    }
+}
+
+// last known server state is saved to display it when server is offline on startup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PinnedServer {
+    pub name: String,
+    pub ip: String,
+    pub port: u32,
+    pub fork: String,
+    pub build: String,
+    pub download: String,
+}
+
+impl PinnedServer {
+    pub fn address(&self) -> Address {
+        Address {
+            ip: IP::Remote(self.ip.clone()),
+            port: self.port,
+        }
+    }
+
+    pub fn version(&self, trusted: &[TrustedDownload]) -> GameVersion {
+        GameVersion {
+            fork: self.fork.clone(),
+            build: self.build.clone(),
+            download: DownloadUrl::new(&self.download, trusted),
+        }
+    }
+
+    // offline server with placeholder data
+    pub fn to_server(&self, trusted: &[TrustedDownload]) -> Server {
+        let mut server = Server::new(
+            self.address(),
+            self.version(trusted),
+            ServerJson {
+                name: self.name.clone(),
+                ..Default::default()
+            },
+        );
+
+        server.offline = true;
+
+        server
+    }
+}
+
+impl From<&Server> for PinnedServer {
+    fn from(server: &Server) -> Self {
+        Self {
+            name: server.name.clone(),
+            ip: server.address.ip.to_string(),
+            port: server.address.port,
+            fork: server.version.fork.clone(),
+            build: server.version.build.clone(),
+            download: match &server.version.download {
+                DownloadUrl::Valid(url) | DownloadUrl::Untrusted(url) => url.to_string(),
+                DownloadUrl::Invalid(url) => url.clone(),
+                DownloadUrl::Local => "".to_owned(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortKey {
+    Name,
+    Build,
+    Map,
+    Players,
+    Fps,
+    Ping,
+    Address,
+}
+
+impl SortKey {
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Name => Self::Build,
+            Self::Build => Self::Map,
+            Self::Map => Self::Players,
+            Self::Players => Self::Fps,
+            Self::Fps => Self::Ping,
+            Self::Ping => Self::Address,
+            Self::Address => Self::Name,
+        }
+    }
+
+    // numbers are more useful in descending order
+    pub const fn descending_by_default(self) -> bool {
+        matches!(self, Self::Players | Self::Fps)
+    }
+
+    pub fn compare(self, a: &Server, b: &Server) -> Ordering {
+        match self {
+            Self::Name => a.name.cmp(&b.name),
+            // builds are numbers most of the time
+            Self::Build => match (
+                a.version.build.parse::<u64>(),
+                b.version.build.parse::<u64>(),
+            ) {
+                (Ok(a), Ok(b)) => a.cmp(&b),
+                _ => a.version.build.cmp(&b.version.build),
+            },
+            Self::Map => a.map.cmp(&b.map),
+            Self::Players => a.players.cmp(&b.players),
+            Self::Fps => a.fps.cmp(&b.fps),
+            Self::Ping => {
+                let ping = |s: &Server| s.ping.map(|p| p.smoothed);
+
+                ping(a).cmp(&ping(b))
+            }
+            // numeric order, ipv4 before ipv6. addresses that are not ip are last
+            Self::Address => {
+                let ip = |s: &Server| s.address.ip.to_string().parse::<IpAddr>().ok();
+
+                ip(a)
+                    .is_none()
+                    .cmp(&ip(b).is_none())
+                    .then_with(|| ip(a).cmp(&ip(b)))
+                    .then_with(|| a.address.ip.to_string().cmp(&b.address.ip.to_string()))
+                    .then_with(|| a.address.port.cmp(&b.address.port))
+            }
+        }
+    }
+}
+
+impl fmt::Display for SortKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Name => "name",
+                Self::Build => "build",
+                Self::Map => "map",
+                Self::Players => "pop",
+                Self::Fps => "fps",
+                Self::Ping => "ping",
+                Self::Address => "address",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SortOrder {
+    pub key: SortKey,
+    pub descending: bool,
+}
+
+impl SortOrder {
+    pub const fn new(key: SortKey) -> Self {
+        Self {
+            key,
+            descending: key.descending_by_default(),
+        }
+    }
+
+    pub const fn next_key(self) -> Self {
+        Self::new(self.key.next())
+    }
+
+    pub const fn reversed(self) -> Self {
+        Self {
+            key: self.key,
+            descending: !self.descending,
+        }
+    }
+
+    pub const fn arrow(&self) -> &'static str {
+        if self.descending {
+            "▼"
+        } else {
+            "▲"
+        }
+    }
+
+    // sorting priorities:
+    //  - server is online
+    //  - server is reachable if sorted by ping, regardless of order
+    //  - selected key
+    //  - server name
+    pub fn compare(&self, a: &Server, b: &Server) -> Ordering {
+        a.offline
+            .cmp(&b.offline)
+            .then_with(|| match self.key {
+                SortKey::Ping => a.ping.is_none().cmp(&b.ping.is_none()),
+                _ => Ordering::Equal,
+            })
+            .then_with(|| {
+                let ordering = self.key.compare(a, b);
+
+                if self.descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            })
+            .then_with(|| a.name.cmp(&b.name))
+    }
+}
+
+impl Default for SortOrder {
+    fn default() -> Self {
+        Self::new(SortKey::Players)
+    }
+}
+
+// returns byte range of first case insensitive occurence of needle in haystack.
+// str::to_lowercase cannot be used for this because it might change byte offsets
+pub fn find_ignore_case(haystack: &str, needle: &str) -> Option<(usize, usize)> {
+    let needle: Vec<char> = needle.chars().flat_map(char::to_lowercase).collect();
+
+    if needle.is_empty() {
+        return Some((0, 0));
+    }
+
+    for (start, _) in haystack.char_indices() {
+        let mut remaining = needle.iter().peekable();
+
+        for (offset, c) in haystack[start..].char_indices() {
+            if !c
+                .to_lowercase()
+                .all(|lower| remaining.next() == Some(&lower))
+            {
+                break;
+            }
+
+            if remaining.peek().is_none() {
+                return Some((start, start + offset + c.len_utf8()));
+            }
+        }
+    }
+
+    None
+}
+
+// byte ranges of case insensitive match of needle in haystack. substring is preferred,
+// otherwise characters of needle are matched in order with gaps: "bxst" matches "Box Station"
+pub fn fuzzy_match(haystack: &str, needle: &str) -> Option<Vec<(usize, usize)>> {
+    if let Some(range) = find_ignore_case(haystack, needle) {
+        return Some(vec![range]);
+    }
+
+    let needle: Vec<char> = needle.chars().flat_map(char::to_lowercase).collect();
+
+    let mut matched = 0;
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+
+    for (start, c) in haystack.char_indices() {
+        let lower: Vec<char> = c.to_lowercase().collect();
+
+        if !needle[matched..].starts_with(&lower) {
+            continue;
+        }
+
+        matched += lower.len();
+
+        let end = start + c.len_utf8();
+
+        // adjacent characters are highlighted as one range
+        match ranges.last_mut() {
+            Some(last) if last.1 == start => last.1 = end,
+            _ => ranges.push((start, end)),
+        }
+
+        if matched == needle.len() {
+            return Some(ranges);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server(name: &str, ping: Option<u64>) -> Server {
+        server_at(name, "127.0.0.1", 7777, ping)
+    }
+
+    fn server_at(name: &str, ip: &str, port: u32, ping: Option<u64>) -> Server {
+        let mut server = Server::new(
+            Address {
+                ip: IP::Remote(ip.to_owned()),
+                port,
+            },
+            GameVersion {
+                fork: "UnityStationDevelop".to_owned(),
+                build: "1".to_owned(),
+                download: DownloadUrl::Local,
+            },
+            ServerJson {
+                name: name.to_owned(),
+                ..Default::default()
+            },
+        );
+
+        server.ping = ping.map(|ms| Ping::new(None, Duration::from_millis(ms)));
+
+        server
+    }
+
+    #[test]
+    fn ping_smoothing() {
+        let first = Ping::new(None, Duration::from_millis(100));
+        assert_eq!(first.smoothed, Duration::from_millis(100));
+
+        let second = Ping::new(Some(first), Duration::from_millis(200));
+        assert_eq!(second.latest, Duration::from_millis(200));
+        assert_eq!(second.smoothed.as_millis(), 130);
+    }
+
+    #[test]
+    fn unreachable_servers_sorted_last() {
+        let mut servers = [
+            server("a", None),
+            server("b", Some(50)),
+            server("c", Some(10)),
+        ];
+
+        let ascending = SortOrder {
+            key: SortKey::Ping,
+            descending: false,
+        };
+
+        servers.sort_by(|a, b| ascending.compare(a, b));
+        let names: Vec<&str> = servers.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["c", "b", "a"]);
+
+        servers.sort_by(|a, b| ascending.reversed().compare(a, b));
+        let names: Vec<&str> = servers.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["b", "c", "a"]);
+    }
+
+    #[test]
+    fn addresses_sorted_numerically() {
+        let mut servers = [
+            server_at("a", "10.0.0.1", 7777, None),
+            server_at("b", "::1", 7777, None),
+            server_at("c", "9.0.0.1", 7778, None),
+            server_at("d", "9.0.0.1", 7777, None),
+            server_at("e", "unknown", 7777, None),
+        ];
+
+        let order = SortOrder {
+            key: SortKey::Address,
+            descending: false,
+        };
+
+        servers.sort_by(|a, b| order.compare(a, b));
+        let names: Vec<&str> = servers.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["d", "c", "a", "b", "e"]);
+    }
+
+    #[test]
+    fn fuzzy_match_prefers_substring() {
+        assert_eq!(fuzzy_match("Box Station", "stat"), Some(vec![(4, 8)]));
+        assert_eq!(fuzzy_match("Box Station", ""), Some(vec![(0, 0)]));
+    }
+
+    #[test]
+    fn fuzzy_match_subsequence() {
+        assert_eq!(
+            fuzzy_match("Box Station", "bxst"),
+            Some(vec![(0, 1), (2, 3), (4, 6)])
+        );
+        assert_eq!(
+            fuzzy_match("Станция Бокс", "сбк"),
+            Some(vec![(0, 2), (15, 17), (19, 21)])
+        );
+        assert_eq!(fuzzy_match("Box Station", "sb"), None);
+    }
 }
@@ -0,0 +1,59 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::datatypes::server::Server;
+
+// notification condition for server or every server of fork. rule matches when server
+// is online and has enough players, notification is sent once rule starts matching
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WatchRule {
+    // server name or address
+    pub server: Option<String>,
+    pub fork: Option<String>,
+    // only checks that server is online if not set
+    pub players: Option<u32>,
+}
+
+impl WatchRule {
+    pub fn validate(&self) -> Result<(), String> {
+        match (&self.server, &self.fork) {
+            (Some(_), None) | (None, Some(_)) => Ok(()),
+            _ => Err("Exactly one of server or fork must be set".to_owned()),
+        }
+    }
+
+    pub fn targets(&self, server: &Server) -> bool {
+        if let Some(query) = &self.server {
+            return server.name.eq_ignore_ascii_case(query) || &server.address.to_string() == query;
+        }
+
+        if let Some(fork) = &self.fork {
+            return &server.version.fork == fork;
+        }
+
+        false
+    }
+
+    pub fn matches(&self, server: &Server) -> bool {
+        !server.offline
+            && self.targets(server)
+            && self.players.map_or(true, |p| server.players >= p)
+    }
+}
+
+impl fmt::Display for WatchRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (&self.server, &self.fork) {
+            (Some(server), _) => write!(f, "server {}", server)?,
+            (None, Some(fork)) => write!(f, "fork {}", fork)?,
+            (None, None) => write!(f, "nothing")?,
+        }
+
+        match self.players {
+            Some(players) => write!(f, " players >= {}", players),
+            None => write!(f, " online"),
+        }
+    }
+}
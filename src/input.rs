@@ -1,6 +1,6 @@
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc;
 use std::thread;
-use std::time::Duration;
 
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers, MouseEventKind};
 
@@ -36,10 +36,6 @@ impl UserInput {
                 KeyEvent {
                     code: KeyCode::Char('c' | 'C'),
                     modifiers: KeyModifiers::CONTROL,
-                }
-                | KeyEvent {
-                    code: KeyCode::Char('q' | 'Q'),
-                    ..
                 } => Some(Self::Quit),
                 KeyEvent {
                     code: KeyCode::Char(c),
@@ -101,25 +97,56 @@ impl UserInput {
     }
 }
 
-pub(crate) enum EventOrTick<I> {
+pub(crate) enum EventOrRedraw<I> {
     Input(I),
-    Tick,
+    Redraw,
+}
+
+// wakes up main loop when state changes. requests are merged until next draw
+pub struct Redraw {
+    pending: AtomicBool,
+    // not set in headless mode
+    tx: Option<mpsc::Sender<EventOrRedraw<UserInput>>>,
 }
 
-pub(crate) fn spawn_input_thread(interval: Duration) -> mpsc::Receiver<EventOrTick<UserInput>> {
-    let (tx, rx) = mpsc::channel();
+impl Redraw {
+    pub(crate) fn new(tx: mpsc::Sender<EventOrRedraw<UserInput>>) -> Self {
+        Self {
+            pending: AtomicBool::new(false),
+            tx: Some(tx),
+        }
+    }
+
+    pub fn disabled() -> Self {
+        Self {
+            pending: AtomicBool::new(false),
+            tx: None,
+        }
+    }
+
+    pub fn request(&self) {
+        if let Some(tx) = &self.tx {
+            if !self.pending.swap(true, Ordering::AcqRel) {
+                // receiver is only dropped on exit
+                let _ = tx.send(EventOrRedraw::Redraw);
+            }
+        }
+    }
+
+    // called before drawing, changes made during draw request another one
+    pub fn clear(&self) {
+        self.pending.store(false, Ordering::Release);
+    }
+}
 
+pub(crate) fn spawn_input_thread(tx: mpsc::Sender<EventOrRedraw<UserInput>>) {
     thread::Builder::new()
         .name("input".to_owned())
         .spawn(move || loop {
-            let event = if event::poll(interval).unwrap() {
-                if let Some(valid_input) = UserInput::from(event::read().unwrap()) {
-                    EventOrTick::Input(valid_input)
-                } else {
-                    EventOrTick::Tick
-                }
-            } else {
-                EventOrTick::Tick
+            // resize and other events still need redraw
+            let event = match UserInput::from(event::read().unwrap()) {
+                Some(valid_input) => EventOrRedraw::Input(valid_input),
+                None => EventOrRedraw::Redraw,
             };
 
             if let Err(e) = tx.send(event) {
@@ -128,6 +155,4 @@ pub(crate) fn spawn_input_thread(interval: Duration) -> mpsc::Receiver<EventOrTi
             }
         })
         .expect("unable to spawn input thread");
-
-    rx
 }
@@ -1,4 +1,8 @@
+// map_or is used instead of is_some_and and is_none_or to support older compilers
+#![allow(clippy::unnecessary_map_or)]
+
 mod app;
+mod cli;
 mod config;
 mod constants;
 mod datatypes;
@@ -8,7 +12,8 @@ mod views;
 
 use std::io;
 use std::sync::atomic::Ordering;
-use std::time::Duration;
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
 
 use tui::{backend::CrosstermBackend, Terminal};
 
@@ -21,7 +26,10 @@ use crossterm::{
 use log::LevelFilter;
 
 use crate::config::AppConfig;
-use crate::input::{spawn_input_thread, EventOrTick};
+use crate::input::{spawn_input_thread, EventOrRedraw, Redraw};
+
+// state changes are drawn at most this often, input is drawn immediately
+const MIN_REDRAW_INTERVAL: Duration = Duration::from_millis(50);
 
 fn setup_panic_hook() {
     #[cfg(not(debug_assertions))]
@@ -92,7 +100,13 @@ fn setup_logger(config: &AppConfig) -> Result<(), io::Error> {
 }
 
 fn _main() -> Result<(), Box<dyn std::error::Error>> {
-    let config: AppConfig = AppConfig::new()?;
+    let mut config: AppConfig = AppConfig::new()?;
+
+    if config.print_config {
+        config.print()?;
+
+        return Ok(());
+    }
 
     setup_logger(&config)?;
 
@@ -100,7 +114,16 @@ fn _main() -> Result<(), Box<dyn std::error::Error>> {
         .enable_all()
         .build()?;
 
-    let mut app = rt.block_on(app::App::new(config));
+    if let Some(command) = config.command.take() {
+        let code = rt.block_on(cli::run(config, command));
+
+        std::process::exit(code);
+    }
+
+    let (tx, rx) = mpsc::channel();
+    let redraw = Arc::new(Redraw::new(tx.clone()));
+
+    let mut app = rt.block_on(app::App::new(config, redraw.clone()));
 
     let mut terminal = {
         enable_raw_mode()?;
@@ -114,17 +137,25 @@ fn _main() -> Result<(), Box<dyn std::error::Error>> {
     };
 
     {
-        let rx = spawn_input_thread(Duration::from_millis(200));
+        spawn_input_thread(tx);
 
         loop {
-            // TODO: only draw when something changed
+            redraw.clear();
+
             terminal.draw(|f| {
                 rt.block_on(app.draw(f));
             })?;
 
+            let last_draw = Instant::now();
+
             match rx.recv()? {
-                EventOrTick::Input(event) => rt.block_on(app.on_input(&event)),
-                EventOrTick::Tick => {}
+                EventOrRedraw::Input(event) => rt.block_on(app.on_input(&event)),
+                EventOrRedraw::Redraw => {
+                    // downloads update state many times per second
+                    if let Some(remaining) = MIN_REDRAW_INTERVAL.checked_sub(last_draw.elapsed()) {
+                        std::thread::sleep(remaining);
+                    }
+                }
             }
 
             if app.stopped {
@@ -1,7 +1,6 @@
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
-use tokio::sync::RwLock;
 use tokio::task::JoinHandle;
 
 use anyhow::Result;
@@ -9,46 +8,95 @@ use anyhow::Result;
 use crate::app::AppAction;
 use crate::config::AppConfig;
 use crate::constants::USER_AGENT;
+use crate::input::Redraw;
+use crate::states::confirm::{ConfirmState, Confirmation};
 use crate::states::events::EventsState;
 use crate::states::help::HelpState;
 use crate::states::help::HotKey;
+use crate::states::lock::StateLock;
 #[cfg(feature = "geolocation")]
 use crate::states::LocationsState;
-use crate::states::{CommitState, ServersState, VersionsState};
+use crate::states::{CommitState, GamesState, ServersState, VersionsState};
 
 pub type TaskResult = Result<()>;
 
 pub struct AppState {
     pub config: AppConfig,
-    pub commits: Arc<RwLock<CommitState>>,
-    pub versions: Arc<RwLock<VersionsState>>,
+    pub commits: Arc<StateLock<CommitState>>,
+    pub versions: Arc<StateLock<VersionsState>>,
     #[cfg(feature = "geolocation")]
-    pub locations: Arc<RwLock<LocationsState>>,
-    pub servers: Arc<RwLock<ServersState>>,
-    pub events: Arc<RwLock<EventsState>>,
+    pub locations: Arc<StateLock<LocationsState>>,
+    pub servers: Arc<StateLock<ServersState>>,
+    pub events: Arc<StateLock<EventsState>>,
+    pub games: Arc<StateLock<GamesState>>,
 
     pub help: Mutex<HelpState>,
+    pub confirm: Mutex<ConfirmState>,
 
     pub client: reqwest::Client,
 
     panic_bool: Arc<AtomicBool>,
+    redraw: Arc<Redraw>,
 }
 
 impl AppState {
-    pub async fn new(config: AppConfig, panic_bool: Arc<AtomicBool>) -> Arc<Self> {
+    pub async fn new(
+        config: AppConfig,
+        panic_bool: Arc<AtomicBool>,
+        redraw: Arc<Redraw>,
+    ) -> Arc<Self> {
+        let instance = Self::create(config, panic_bool, redraw).await;
+
+        instance.events.write().await.run(instance.clone()).await;
+        instance.servers.write().await.run(instance.clone()).await;
         #[cfg(feature = "geolocation")]
-        let locations = Arc::new(RwLock::new(LocationsState::new(&config).await));
-        let versions = Arc::new(RwLock::new(VersionsState::new(&config).await));
-        let servers = Arc::new(RwLock::new(ServersState::new(&config).await));
-        let events = Arc::new(RwLock::new(EventsState::new(&config).await));
-
-        let instance = Arc::new(Self {
-            commits: Arc::new(RwLock::new(CommitState::new().await)),
-            versions: versions.clone(),
+        instance.locations.write().await.run(instance.clone()).await;
+        instance.versions.write().await.run(instance.clone()).await;
+
+        instance
+    }
+
+    // without tui: no periodic server list updates, events are printed. waits until
+    // installations are found
+    pub async fn new_headless(config: AppConfig) -> Result<Arc<Self>> {
+        let instance = Self::create(
+            config,
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(Redraw::disabled()),
+        )
+        .await;
+
+        instance.events.write().await.run_headless();
+        VersionsState::run_headless(instance.clone()).await?;
+
+        Ok(instance)
+    }
+
+    async fn create(
+        config: AppConfig,
+        panic_bool: Arc<AtomicBool>,
+        redraw: Arc<Redraw>,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            commits: Arc::new(StateLock::new(CommitState::new().await, redraw.clone())),
+            versions: Arc::new(StateLock::new(
+                VersionsState::new(&config).await,
+                redraw.clone(),
+            )),
             #[cfg(feature = "geolocation")]
-            locations: locations.clone(),
-            servers: servers.clone(),
-            events: events.clone(),
+            locations: Arc::new(StateLock::new(
+                LocationsState::new(&config).await,
+                redraw.clone(),
+            )),
+            servers: Arc::new(StateLock::new(
+                ServersState::new(&config).await,
+                redraw.clone(),
+            )),
+            events: Arc::new(StateLock::new(
+                EventsState::new(&config).await,
+                redraw.clone(),
+            )),
+            games: Arc::new(StateLock::new(GamesState::new(), redraw.clone())),
             config,
             client: reqwest::Client::builder()
                 .user_agent(USER_AGENT)
@@ -56,51 +104,104 @@ impl AppState {
                 .expect("creating client"),
 
             help: Mutex::new(HelpState::new()),
+            confirm: Mutex::new(ConfirmState::new()),
 
             panic_bool,
-        });
-
-        events.write().await.run(instance.clone()).await;
-        servers.write().await.run(instance.clone()).await;
-        #[cfg(feature = "geolocation")]
-        locations.write().await.run(instance.clone()).await;
-        versions.write().await.run(instance.clone()).await;
-
-        instance
+            redraw,
+        })
     }
 
     pub async fn on_action(&self, action: &AppAction, app: Arc<AppState>) {
         log::debug!("action: {:?}", &action);
 
-        let f = match action {
-            AppAction::ConnectToServer { version, address } => Some(tokio::spawn(
-                VersionsState::launch(Arc::clone(&app), version.clone(), Some(address.clone())),
-            )),
-            AppAction::InstallVersion(version) => Some(tokio::spawn(VersionsState::install(
-                Arc::clone(&app),
-                version.clone(),
-            ))),
-            AppAction::LaunchVersion(version) => Some(tokio::spawn(VersionsState::launch(
-                Arc::clone(&app),
-                version.clone(),
-                None,
-            ))),
-            AppAction::AbortVersionInstallation(version) => Some(tokio::spawn(
-                VersionsState::abort_installation(Arc::clone(&app), version.clone()),
-            )),
-            AppAction::UninstallVersion(version) => Some(tokio::spawn(VersionsState::uninstall(
-                Arc::clone(&app),
-                version.clone(),
-            ))),
-
-            _ => None,
-        };
+        let f =
+            match action {
+                AppAction::ConnectToServer { version, address } => Some(tokio::spawn(
+                    VersionsState::launch(Arc::clone(&app), version.clone(), Some(address.clone())),
+                )),
+                AppAction::InstallVersion(version) => Some(tokio::spawn(VersionsState::enqueue(
+                    Arc::clone(&app),
+                    version.clone(),
+                    false,
+                ))),
+                AppAction::LaunchVersion(version) => Some(tokio::spawn(VersionsState::launch(
+                    Arc::clone(&app),
+                    version.clone(),
+                    None,
+                ))),
+                AppAction::AbortVersionInstallation(version) => Some(tokio::spawn(
+                    VersionsState::abort_installation(Arc::clone(&app), version.clone()),
+                )),
+                AppAction::UninstallVersion(version) => Some(tokio::spawn(
+                    VersionsState::uninstall(Arc::clone(&app), version.clone()),
+                )),
+                AppAction::VerifyVersion(version) => Some(tokio::spawn(
+                    VersionsState::verify_task(Arc::clone(&app), version.clone()),
+                )),
+                AppAction::RepairVersion(version) => Some(tokio::spawn(VersionsState::repair(
+                    Arc::clone(&app),
+                    version.clone(),
+                ))),
+
+                _ => None,
+            };
 
         if let Some(f) = f {
             self.watch_task(f).await;
         }
     }
 
+    // destructive actions need confirmation unless disabled in config. second game
+    // connecting to same server is always confirmed
+    pub async fn confirmation(&self, action: &AppAction) -> Option<Confirmation> {
+        let (title, details) = match action {
+            AppAction::ConnectToServer { address, .. } => {
+                let games = self.games.read().await;
+                let running = games.running_on(address)?;
+
+                (
+                    format!("Connect to {} again?", address),
+                    vec![format!(
+                        "{} is already running and connected to this server (pid {})",
+                        running.version,
+                        running
+                            .pid
+                            .map_or_else(|| "?".to_owned(), |p| p.to_string())
+                    )],
+                )
+            }
+            _ if !self.config.confirm_destructive => return None,
+            AppAction::UninstallVersion(version) => (
+                format!("Uninstall {}?", version),
+                self.versions
+                    .read()
+                    .await
+                    .describe_uninstall(&self.config, version)?,
+            ),
+            AppAction::RepairVersion(version) => (
+                format!("Repair {}?", version),
+                self.versions
+                    .read()
+                    .await
+                    .describe_repair(&self.config, version)?,
+            ),
+            AppAction::AbortVersionInstallation(version) => {
+                self.versions
+                    .read()
+                    .await
+                    .describe_abort(&self.config, version)
+                    .await?
+            }
+            _ => return None,
+        };
+
+        Some(Confirmation {
+            action: action.clone(),
+            title,
+            details,
+        })
+    }
+
     pub fn display_help(&self, view_name: &str, keys: &[HotKey]) {
         let mut help = self.help.lock().unwrap();
         help.set_name(view_name);
@@ -111,6 +212,7 @@ impl AppState {
         tokio::spawn(Self::wrap_task(
             task,
             self.panic_bool.clone(),
+            self.redraw.clone(),
             self.events.clone(),
         ));
     }
@@ -118,15 +220,17 @@ impl AppState {
     async fn wrap_task(
         task: JoinHandle<TaskResult>,
         panic_bool: Arc<AtomicBool>,
-        events: Arc<RwLock<EventsState>>,
+        redraw: Arc<Redraw>,
+        events: Arc<StateLock<EventsState>>,
     ) {
         match task.await {
             Err(err) => {
                 log::warn!("join error: {:?}", &err);
 
                 if err.is_panic() {
-                    log::error!("error is panic, setting panic to exit on next tick");
+                    log::error!("error is panic, setting panic to exit on next redraw");
                     panic_bool.store(true, Ordering::Relaxed);
+                    redraw.request();
                 }
             }
             Ok(result) => {
@@ -2,7 +2,6 @@ use std::sync::Arc;
 
 use anyhow::Context;
 
-use crate::constants::GITHUB_REPO_COMMIT_ENDPOINT_URL;
 use crate::datatypes::commit::{Commit, CommitsJson};
 use crate::states::app::{AppState, TaskResult};
 
@@ -28,7 +27,7 @@ impl CommitState {
 
         let commit_range = app
             .client
-            .get(GITHUB_REPO_COMMIT_ENDPOINT_URL)
+            .get(app.config.commits_url.clone())
             .headers(headers)
             .send()
             .await
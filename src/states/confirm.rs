@@ -0,0 +1,28 @@
+use crate::app::AppAction;
+
+// destructive action waiting for user decision
+#[derive(Debug, Clone)]
+pub struct Confirmation {
+    pub action: AppAction,
+    pub title: String,
+    // what is going to be removed and how large it is
+    pub details: Vec<String>,
+}
+
+pub struct ConfirmState {
+    pub pending: Option<Confirmation>,
+}
+
+impl ConfirmState {
+    pub fn new() -> Self {
+        Self { pending: None }
+    }
+
+    pub fn set(&mut self, confirmation: Confirmation) {
+        self.pending = Some(confirmation);
+    }
+
+    pub fn take(&mut self) -> Option<Confirmation> {
+        self.pending.take()
+    }
+}
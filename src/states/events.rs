@@ -1,7 +1,13 @@
+use std::collections::VecDeque;
 use std::fmt;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+use anyhow::Context;
+
+use chrono::{DateTime, Local};
+
 use tokio::sync::{mpsc, Mutex};
 use tokio::time::sleep;
 
@@ -24,8 +30,58 @@ impl fmt::Display for AppEvent {
     }
 }
 
+// older records are dropped
+const MAX_HISTORY: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Info => f.pad("info"),
+            Self::Error => f.pad("error"),
+        }
+    }
+}
+
+// copy of event kept in history. errors are stored as text because they cannot be cloned
+#[derive(Debug, Clone)]
+pub struct EventRecord {
+    pub time: DateTime<Local>,
+    pub severity: Severity,
+    pub message: String,
+    // error context chain, outermost first
+    pub chain: Vec<String>,
+}
+
+impl fmt::Display for EventRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} [{}] {}",
+            self.time.format("%Y-%m-%d %H:%M:%S"),
+            self.severity,
+            self.message
+        )?;
+
+        for (i, cause) in self.chain.iter().enumerate().skip(1) {
+            write!(f, "\n  {}: {}", i, cause)?;
+        }
+
+        Ok(())
+    }
+}
+
 pub struct EventsState {
     pub current_event: Option<AppEvent>,
+    // every event, not rate limited
+    history: std::sync::Mutex<VecDeque<EventRecord>>,
+    // headless mode: events are printed instead of being displayed
+    print: bool,
     events: mpsc::UnboundedSender<AppEvent>,
     events_recv: Option<mpsc::UnboundedReceiver<AppEvent>>,
 }
@@ -36,6 +92,8 @@ impl EventsState {
 
         Self {
             current_event: None,
+            history: std::sync::Mutex::new(VecDeque::with_capacity(MAX_HISTORY)),
+            print: false,
             events,
             events_recv: Some(events_recv),
         }
@@ -49,9 +107,66 @@ impl EventsState {
         .await;
     }
 
+    pub fn run_headless(&mut self) {
+        self.print = true;
+    }
+
+    fn record(&self, severity: Severity, message: String, chain: Vec<String>) {
+        let mut history = self.history.lock().unwrap();
+
+        if history.len() == MAX_HISTORY {
+            history.pop_front();
+        }
+
+        history.push_back(EventRecord {
+            time: Local::now(),
+            severity,
+            message,
+            chain,
+        });
+    }
+
+    // newest last
+    pub fn history(&self, errors_only: bool) -> Vec<EventRecord> {
+        self.history
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|r| !errors_only || r.severity == Severity::Error)
+            .cloned()
+            .collect()
+    }
+
+    // writes history to timestamped file in directory
+    pub async fn export(&self, dir: &Path, errors_only: bool) -> anyhow::Result<PathBuf> {
+        let path = dir.join(format!(
+            "events-{}.log",
+            Local::now().format("%Y%m%d-%H%M%S")
+        ));
+
+        let mut data = String::new();
+        for record in self.history(errors_only) {
+            data.push_str(&record.to_string());
+            data.push('\n');
+        }
+
+        tokio::fs::write(&path, data)
+            .await
+            .with_context(|| format!("Unable to write {}", path.display()))?;
+
+        Ok(path)
+    }
+
     pub async fn event(&self, message: &str) {
         log::debug!("event: {}", message);
 
+        self.record(Severity::Info, message.to_owned(), Vec::new());
+
+        if self.print {
+            eprintln!("{}", message);
+            return;
+        }
+
         if let Err(err) = self.events.send(AppEvent::Event(message.to_owned())) {
             log::error!("error sending event: {}", err);
         }
@@ -60,6 +175,17 @@ impl EventsState {
     pub async fn error(&self, err: anyhow::Error) {
         log::debug!("error: {:#}", &err);
 
+        self.record(
+            Severity::Error,
+            err.to_string(),
+            err.chain().map(ToString::to_string).collect(),
+        );
+
+        if self.print {
+            eprintln!("error: {:#}", err);
+            return;
+        }
+
         if let Err(err) = self.events.send(AppEvent::Error(err)) {
             log::error!("error sending error: {}", err);
         }
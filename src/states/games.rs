@@ -0,0 +1,429 @@
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Stdio};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Context};
+
+use tokio::fs;
+use tokio::process::{Child, Command};
+use tokio::sync::mpsc;
+
+use crate::datatypes::game_version::GameVersion;
+use crate::datatypes::server::Address;
+use crate::states::app::{AppState, TaskResult};
+
+// log files kept per build, older ones are removed on launch
+const KEPT_LOGS: usize = 5;
+// exited games kept in list
+const KEPT_EXITED: usize = 10;
+// log size and uptime are updated this often
+const LOG_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameStatus {
+    Running,
+    // None if terminated by signal
+    Exited(Option<i32>),
+}
+
+impl GameStatus {
+    fn from_exit(status: ExitStatus) -> Self {
+        Self::Exited(status.code())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum StopSignal {
+    // lets game exit gracefully, same as kill on windows
+    Terminate,
+    Kill,
+}
+
+#[derive(Debug)]
+pub struct Game {
+    pub id: u64,
+    pub version: GameVersion,
+    pub address: Option<Address>,
+    pub pid: Option<u32>,
+    pub status: GameStatus,
+    pub log_path: PathBuf,
+    // updated while game writes output
+    pub log_size: u64,
+    started: Instant,
+    finished: Option<Instant>,
+    // child is owned by watch task
+    stop: mpsc::UnboundedSender<StopSignal>,
+}
+
+impl Game {
+    pub fn uptime(&self) -> Duration {
+        self.finished.unwrap_or_else(Instant::now) - self.started
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.status == GameStatus::Running
+    }
+}
+
+// games launched during this session, oldest first
+pub struct GamesState {
+    pub items: Vec<Game>,
+    next_id: u64,
+    // log file shown in log view
+    pub viewed_log: Option<PathBuf>,
+}
+
+impl GamesState {
+    pub fn new() -> Self {
+        Self {
+            items: Vec::new(),
+            next_id: 0,
+            viewed_log: None,
+        }
+    }
+
+    pub fn get(&self, id: u64) -> Option<&Game> {
+        self.items.iter().find(|g| g.id == id)
+    }
+
+    fn get_mut(&mut self, id: u64) -> Option<&mut Game> {
+        self.items.iter_mut().find(|g| g.id == id)
+    }
+
+    pub fn running(&self) -> impl Iterator<Item = &Game> {
+        self.items.iter().filter(|g| g.is_running())
+    }
+
+    pub fn running_on(&self, address: &Address) -> Option<&Game> {
+        self.running().find(|g| g.address.as_ref() == Some(address))
+    }
+
+    pub fn stop(&self, id: u64, signal: StopSignal) -> anyhow::Result<()> {
+        let game = self
+            .get(id)
+            .filter(|g| g.is_running())
+            .ok_or_else(|| anyhow!("Game is not running"))?;
+
+        game.stop
+            .send(signal)
+            .map_err(|_| anyhow!("{} is exiting", game.version))
+    }
+
+    pub fn by_log(&self, path: &Path) -> Option<&Game> {
+        self.items.iter().find(|g| g.log_path == path)
+    }
+
+    // log of newest game of version, including games from previous sessions
+    pub fn latest_log(&self, logs_dir: &Path, version: &GameVersion) -> Option<PathBuf> {
+        if let Some(game) = self.items.iter().rev().find(|g| &g.version == version) {
+            return Some(game.log_path.clone());
+        }
+
+        Self::log_files(&logs_dir.join(PathBuf::from(version.clone())))
+            .ok()?
+            .pop()
+    }
+
+    // oldest first, names are timestamps
+    fn log_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+        let mut files: Vec<PathBuf> = std::fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().map_or(false, |ext| ext == "log"))
+            .collect();
+
+        files.sort();
+
+        Ok(files)
+    }
+
+    // creates log file for new launch and removes old ones. logs of running games are kept
+    async fn create_log(
+        logs_dir: &Path,
+        version: &GameVersion,
+        in_use: &[PathBuf],
+    ) -> anyhow::Result<(PathBuf, std::fs::File)> {
+        let dir = logs_dir.join(PathBuf::from(version.clone()));
+
+        fs::create_dir_all(&dir)
+            .await
+            .with_context(|| format!("Unable to create log directory {}", dir.display()))?;
+
+        let files = Self::log_files(&dir)?;
+
+        for old in files
+            .iter()
+            .filter(|path| !in_use.contains(path))
+            .take((files.len() + 1).saturating_sub(KEPT_LOGS))
+        {
+            if let Err(err) = fs::remove_file(old).await {
+                log::warn!("unable to remove old log {}: {}", old.display(), err);
+            }
+        }
+
+        // never opens log of other launch, even if started in same millisecond
+        loop {
+            let path = dir.join(format!(
+                "{}.log",
+                chrono::Local::now().format("%Y%m%d-%H%M%S%.3f")
+            ));
+
+            match std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(file) => return Ok((path, file)),
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                    tokio::time::sleep(Duration::from_millis(1)).await;
+                }
+                Err(err) => {
+                    return Err(err)
+                        .with_context(|| format!("Unable to create log {}", path.display()))
+                }
+            }
+        }
+    }
+
+    // output goes directly to file so game keeps running after rshub exits
+    pub async fn spawn(
+        app: Arc<AppState>,
+        mut command: Command,
+        version: GameVersion,
+        address: Option<Address>,
+    ) -> TaskResult {
+        let in_use: Vec<PathBuf> = app
+            .games
+            .read()
+            .await
+            .running()
+            .map(|g| g.log_path.clone())
+            .collect();
+
+        let (log_path, log) =
+            Self::create_log(&app.config.dirs.logs_dir, &version, &in_use).await?;
+
+        let child = command
+            .stdin(Stdio::null())
+            .stdout(log.try_clone()?)
+            .stderr(log)
+            .spawn()
+            .with_context(|| "Unable to launch installation")?;
+
+        let (stop, stop_recv) = mpsc::unbounded_channel();
+
+        let id = {
+            let mut games = app.games.write().await;
+
+            let id = games.next_id;
+            games.next_id += 1;
+
+            games.items.push(Game {
+                id,
+                version,
+                address,
+                pid: child.id(),
+                status: GameStatus::Running,
+                log_path,
+                log_size: 0,
+                started: Instant::now(),
+                finished: None,
+                stop,
+            });
+
+            id
+        };
+
+        app.watch_task(tokio::spawn(Self::watch_game(
+            app.clone(),
+            id,
+            child,
+            stop_recv,
+        )))
+        .await;
+
+        Ok(())
+    }
+
+    fn send_signal(child: &mut Child, signal: StopSignal) -> io::Result<()> {
+        match signal {
+            #[cfg(unix)]
+            StopSignal::Terminate => match child.id() {
+                Some(pid) => {
+                    // SAFETY: pid belongs to child that was not reaped yet
+                    if unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) } == 0 {
+                        Ok(())
+                    } else {
+                        Err(io::Error::last_os_error())
+                    }
+                }
+                None => Ok(()),
+            },
+            _ => child.start_kill(),
+        }
+    }
+
+    async fn watch_game(
+        app: Arc<AppState>,
+        id: u64,
+        mut child: Child,
+        mut stop_recv: mpsc::UnboundedReceiver<StopSignal>,
+    ) -> TaskResult {
+        let log_path = match app.games.read().await.get(id) {
+            Some(game) => game.log_path.clone(),
+            None => return Ok(()),
+        };
+
+        let mut interval = tokio::time::interval(LOG_POLL_INTERVAL);
+
+        let status = loop {
+            tokio::select! {
+                status = child.wait() => break status?,
+                Some(signal) = stop_recv.recv() => {
+                    log::info!("stopping game {}: {:?}", id, signal);
+
+                    if let Err(err) = Self::send_signal(&mut child, signal) {
+                        app.events
+                            .read()
+                            .await
+                            .error(anyhow!("Unable to stop game: {}", err))
+                            .await;
+                    }
+                }
+                _ = interval.tick() => {
+                    let log_size = fs::metadata(&log_path).await.map_or(0, |m| m.len());
+
+                    // written even if size did not change to redraw uptime
+                    if let Some(game) = app.games.write().await.get_mut(id) {
+                        game.log_size = log_size;
+                    }
+                }
+            }
+        };
+
+        let version = {
+            let mut games = app.games.write().await;
+
+            let version = match games.get_mut(id) {
+                Some(game) => {
+                    game.status = GameStatus::from_exit(status);
+                    game.finished = Some(Instant::now());
+                    game.log_size = fs::metadata(&log_path).await.map_or(0, |m| m.len());
+
+                    game.version.clone()
+                }
+                None => return Ok(()),
+            };
+
+            games.trim_exited();
+
+            version
+        };
+
+        if status.success() {
+            app.events
+                .read()
+                .await
+                .event(&format!("{} exited", version))
+                .await;
+
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "{} exited with {}, log: {}",
+                version,
+                status,
+                log_path.display()
+            ))
+        }
+    }
+
+    fn trim_exited(&mut self) {
+        let exited = self.items.iter().filter(|g| !g.is_running()).count();
+        let mut excess = exited.saturating_sub(KEPT_EXITED);
+
+        self.items.retain(|g| {
+            if excess != 0 && !g.is_running() {
+                excess -= 1;
+                false
+            } else {
+                true
+            }
+        });
+    }
+}
+
+// last lines of file, reads at most max_bytes from the end
+pub fn tail(path: &Path, max_bytes: u64) -> io::Result<Vec<String>> {
+    let mut file = std::fs::File::open(path)?;
+
+    let len = file.metadata()?.len();
+    let start = len.saturating_sub(max_bytes);
+
+    file.seek(SeekFrom::Start(start))?;
+
+    let mut data = Vec::with_capacity((len - start) as usize);
+    file.read_to_end(&mut data)?;
+
+    let text = String::from_utf8_lossy(&data);
+    let mut lines: Vec<String> = text.lines().map(str::to_owned).collect();
+
+    // first line is probably cut
+    if start != 0 && !lines.is_empty() {
+        lines.remove(0);
+    }
+
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::datatypes::game_version::DownloadUrl;
+
+    #[tokio::test]
+    async fn create_log_keeps_logs_in_use() {
+        let logs_dir = std::env::temp_dir().join(format!("rshub-logs-{}", std::process::id()));
+        std::fs::remove_dir_all(&logs_dir).ok();
+
+        let version = GameVersion {
+            fork: "Test".to_owned(),
+            build: "1".to_owned(),
+            download: DownloadUrl::Local,
+        };
+        let dir = logs_dir.join("Test").join("1");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let old: Vec<PathBuf> = (0..6)
+            .map(|i| dir.join(format!("20200101-00000{}.log", i)))
+            .collect();
+        for path in &old {
+            std::fs::write(path, b"").unwrap();
+        }
+
+        // oldest one belongs to running game
+        let (first, _) = GamesState::create_log(&logs_dir, &version, &old[..1])
+            .await
+            .unwrap();
+        let (second, _) = GamesState::create_log(&logs_dir, &version, &old[..1])
+            .await
+            .unwrap();
+
+        assert_ne!(first, second);
+        assert!(old[0].exists());
+        assert_eq!(
+            GamesState::log_files(&dir).unwrap(),
+            [
+                old[0].clone(),
+                old[4].clone(),
+                old[5].clone(),
+                first,
+                second
+            ]
+        );
+
+        std::fs::remove_dir_all(&logs_dir).ok();
+    }
+}
@@ -47,7 +47,7 @@ impl LocationsState {
     }
 
     pub async fn resolve(&mut self, ip: &IP) {
-        if self.items.get(ip).is_some() {
+        if self.items.contains_key(ip) {
             return;
         }
 
@@ -0,0 +1,58 @@
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::input::Redraw;
+
+// RwLock that requests redraw when write guard is released
+pub struct StateLock<T> {
+    lock: RwLock<T>,
+    redraw: Arc<Redraw>,
+}
+
+impl<T> StateLock<T> {
+    pub fn new(value: T, redraw: Arc<Redraw>) -> Self {
+        Self {
+            lock: RwLock::new(value),
+            redraw,
+        }
+    }
+
+    pub async fn read(&self) -> RwLockReadGuard<'_, T> {
+        self.lock.read().await
+    }
+
+    pub async fn write(&self) -> StateWriteGuard<'_, T> {
+        StateWriteGuard {
+            guard: self.lock.write().await,
+            redraw: &self.redraw,
+        }
+    }
+}
+
+pub struct StateWriteGuard<'a, T> {
+    guard: RwLockWriteGuard<'a, T>,
+    redraw: &'a Redraw,
+}
+
+impl<T> Deref for StateWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T> DerefMut for StateWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl<T> Drop for StateWriteGuard<'_, T> {
+    // guard is still held here, drawing waits for it to be released
+    fn drop(&mut self) {
+        self.redraw.request();
+    }
+}
@@ -1,14 +1,18 @@
 pub mod app;
 pub mod commits;
+pub mod confirm;
 pub mod events;
+pub mod games;
 pub mod help;
 #[cfg(feature = "geolocation")]
 pub mod locations;
+pub mod lock;
 pub mod servers;
 pub mod versions;
 
 pub use app::AppState;
 pub use commits::CommitState;
+pub use games::GamesState;
 #[cfg(feature = "geolocation")]
 pub use locations::LocationsState;
 pub use servers::ServersState;
@@ -1,29 +1,331 @@
-use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use anyhow::Context;
 
+use serde::{Deserialize, Serialize};
+
+use tokio::fs;
+use tokio::net::TcpStream;
+use tokio::process::Command;
+use tokio::sync::{Notify, Semaphore};
+
 use crate::config::AppConfig;
-use crate::constants::SERVER_LIST_URL;
+use crate::datatypes::export::{ExportFormat, ServerRecord};
 use crate::datatypes::game_version::{DownloadUrl, GameVersion};
 use crate::datatypes::geolocation::IP;
-use crate::datatypes::server::{Address, Server, ServerListJson};
+use crate::datatypes::history::History;
+use crate::datatypes::installation::{Installation, InstallationKind};
+use crate::datatypes::server::{Address, Ping, PinnedServer, Server, ServerListJson, SortOrder};
+use crate::datatypes::watch::WatchRule;
 use crate::states::app::{AppState, TaskResult};
 use crate::states::versions::VersionsState;
 
+// server list preferences preserved between launches
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct ServersSettings {
+    sort: SortOrder,
+    pinned: Vec<PinnedServer>,
+    // rules created with hotkey, rules from config are not saved here
+    watched: Vec<WatchRule>,
+}
+
 pub struct ServersState {
     pub items: Vec<Server>,
     update_interval: Duration,
+    // notified after every server list update
+    updated: Arc<Notify>,
+    settings: ServersSettings,
+    settings_path: PathBuf,
+    pub history: History,
+    // rule and server pairs that matched during last update. unset before first update,
+    // servers matching at startup are not reported
+    watch_matched: Option<HashSet<(String, Address)>>,
 }
 
 impl ServersState {
     pub async fn new(config: &AppConfig) -> Self {
+        let settings_path = config.dirs.data_dir.join("servers.json");
+
+        let settings = Self::load_settings(&settings_path).await;
+
+        let history = History::load(
+            &config.dirs.data_dir.join("history.ndjson"),
+            config.history_max_size,
+            chrono::Duration::seconds(config.history_interval as i64),
+        )
+        .await;
+
         Self {
-            items: Vec::new(),
+            // pinned servers are displayed even if they are not in server list
+            items: settings
+                .pinned
+                .iter()
+                .map(|p| p.to_server(&config.trusted_downloads))
+                .collect(),
             update_interval: Duration::from_secs(config.update_interval),
+            updated: Arc::new(Notify::new()),
+            settings,
+            settings_path,
+            history,
+            watch_matched: None,
+        }
+    }
+
+    async fn load_settings(path: &Path) -> ServersSettings {
+        let data = match fs::read(path).await {
+            Ok(data) => data,
+            Err(err) => {
+                log::debug!("unable to read {}: {}", path.display(), err);
+                return ServersSettings::default();
+            }
+        };
+
+        serde_json::from_slice(&data).unwrap_or_else(|err| {
+            log::error!("bad server settings file {}: {}", path.display(), err);
+            ServersSettings::default()
+        })
+    }
+
+    async fn save_settings(&self) -> anyhow::Result<()> {
+        fs::write(&self.settings_path, serde_json::to_vec(&self.settings)?)
+            .await
+            .with_context(|| format!("Unable to save {}", self.settings_path.display()))
+    }
+
+    pub fn sort_order(&self) -> SortOrder {
+        self.settings.sort
+    }
+
+    pub async fn set_sort_order(&mut self, order: SortOrder) -> anyhow::Result<()> {
+        self.settings.sort = order;
+        self.sort();
+
+        self.save_settings().await
+    }
+
+    pub fn is_pinned(&self, server: &Server) -> bool {
+        self.settings
+            .pinned
+            .iter()
+            .any(|p| p.address() == server.address)
+    }
+
+    pub async fn toggle_pin(&mut self, address: &Address) -> anyhow::Result<()> {
+        let before = self.settings.pinned.len();
+
+        self.settings.pinned.retain(|p| &p.address() != address);
+
+        if self.settings.pinned.len() == before {
+            if let Some(server) = self.items.iter().find(|s| &s.address == address) {
+                self.settings.pinned.push(PinnedServer::from(server));
+            }
+        }
+
+        self.sort();
+
+        self.save_settings().await
+    }
+
+    fn watch_rules<'a>(&'a self, config: &'a AppConfig) -> impl Iterator<Item = &'a WatchRule> {
+        config.watch.iter().chain(self.settings.watched.iter())
+    }
+
+    pub fn is_watched(&self, config: &AppConfig, server: &Server) -> bool {
+        self.watch_rules(config).any(|r| r.targets(server))
+    }
+
+    // adds or removes hotkey rule of server
+    pub async fn toggle_watch(&mut self, address: &Address, players: u32) -> anyhow::Result<()> {
+        let target = address.to_string();
+        let before = self.settings.watched.len();
+
+        self.settings
+            .watched
+            .retain(|r| r.server.as_ref() != Some(&target));
+
+        if self.settings.watched.len() == before {
+            self.settings.watched.push(WatchRule {
+                server: Some(target),
+                fork: None,
+                players: if players == 0 { None } else { Some(players) },
+            });
+        }
+
+        self.save_settings().await
+    }
+
+    // notifies about rules that started matching since last update
+    async fn check_watches(&mut self, app: &AppState) {
+        let previous = self.watch_matched.take();
+        let mut matched = HashSet::new();
+        let mut notifications = Vec::new();
+
+        for rule in self.watch_rules(&app.config) {
+            for server in self.items.iter().filter(|s| rule.matches(s)) {
+                let key = (rule.to_string(), server.address.clone());
+
+                if let Some(previous) = &previous {
+                    if !previous.contains(&key) {
+                        notifications.push((rule.clone(), server.clone()));
+                    }
+                }
+
+                matched.insert(key);
+            }
+        }
+
+        self.watch_matched = Some(matched);
+
+        for (rule, server) in notifications {
+            Self::notify_watch(app, &rule, &server).await;
+        }
+    }
+
+    async fn notify_watch(app: &AppState, rule: &WatchRule, server: &Server) {
+        app.events
+            .read()
+            .await
+            .event(&format!(
+                "Watch {}: {} has {} players on {}",
+                rule, server.name, server.players, server.map
+            ))
+            .await;
+
+        if app.config.watch_bell {
+            let mut stdout = std::io::stdout();
+
+            if let Err(err) = stdout.write_all(b"\x07").and_then(|_| stdout.flush()) {
+                log::warn!("unable to ring bell: {}", err);
+            }
         }
+
+        if let Some(command) = &app.config.watch_command {
+            if let Err(err) = Self::run_watch_command(command, rule, server) {
+                app.events.read().await.error(err).await;
+            }
+        }
+    }
+
+    fn run_watch_command(command: &str, rule: &WatchRule, server: &Server) -> anyhow::Result<()> {
+        #[cfg(windows)]
+        let mut shell = {
+            let mut shell = Command::new("cmd");
+            shell.arg("/C");
+            shell
+        };
+        #[cfg(not(windows))]
+        let mut shell = {
+            let mut shell = Command::new("sh");
+            shell.arg("-c");
+            shell
+        };
+
+        let mut child = shell
+            .arg(command)
+            .env("RSHUB_WATCH_RULE", rule.to_string())
+            .env("RSHUB_SERVER_NAME", &server.name)
+            .env("RSHUB_SERVER_ADDRESS", server.address.to_string())
+            .env("RSHUB_SERVER_PLAYERS", server.players.to_string())
+            .env("RSHUB_SERVER_MAP", &server.map)
+            .env("RSHUB_SERVER_GAMEMODE", &server.gamemode)
+            .env("RSHUB_SERVER_FORK", &server.version.fork)
+            .env("RSHUB_SERVER_BUILD", &server.version.build)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .with_context(|| format!("Unable to run watch command {}", command))?;
+
+        // reap process
+        tokio::spawn(async move {
+            match child.wait().await {
+                Ok(status) if !status.success() => {
+                    log::warn!("watch command exited with {}", status)
+                }
+                Err(err) => log::warn!("watch command failed: {}", err),
+                _ => {}
+            }
+        });
+
+        Ok(())
+    }
+
+    // pinned servers might change address. if pinned address is not online anymore but
+    // there is exactly one online server with same name, assume it moved
+    async fn update_pins(&mut self) -> anyhow::Result<()> {
+        let mut changed = false;
+
+        for i in 0..self.settings.pinned.len() {
+            let pin = &self.settings.pinned[i];
+            let old_address = pin.address();
+
+            if self
+                .items
+                .iter()
+                .any(|s| !s.offline && s.address == old_address)
+            {
+                continue;
+            }
+
+            let mut same_name = self
+                .items
+                .iter()
+                .filter(|s| !s.offline && s.name == pin.name);
+
+            // unclear which one it is if several servers share name
+            let moved = match (same_name.next(), same_name.next()) {
+                (Some(moved), None) => moved,
+                _ => continue,
+            };
+
+            // would duplicate existing pin
+            if self
+                .settings
+                .pinned
+                .iter()
+                .any(|p| p.address() == moved.address)
+            {
+                continue;
+            }
+
+            log::info!(
+                "pinned server {} moved: {} -> {}",
+                pin.name,
+                old_address,
+                moved.address
+            );
+
+            self.settings.pinned[i] = PinnedServer::from(moved);
+
+            self.items
+                .retain(|s| !(s.offline && s.address == old_address));
+
+            changed = true;
+        }
+
+        if changed {
+            self.save_settings().await?;
+        }
+
+        Ok(())
+    }
+
+    fn sort(&mut self) {
+        let order = self.settings.sort;
+        let pinned: Vec<Address> = self.settings.pinned.iter().map(|p| p.address()).collect();
+
+        self.items.sort_by(|a, b| {
+            pinned
+                .contains(&b.address)
+                .cmp(&pinned.contains(&a.address))
+                .then_with(|| order.compare(a, b))
+        });
     }
 
     pub async fn run(&mut self, app: Arc<AppState>) {
@@ -33,7 +335,7 @@ impl ServersState {
             let version = GameVersion {
                 fork: "evil-exploit".to_owned(),
                 build: 666.to_string(),
-                download: DownloadUrl::new("http://evil.exploit"),
+                download: DownloadUrl::new("http://evil.exploit", &app.config.trusted_downloads),
             };
 
             self.items.push(Server {
@@ -45,6 +347,7 @@ impl ServersState {
                 offline: true,
                 version: version.clone(),
                 fps: 42,
+                ping: None,
                 time: "13:37".to_owned(),
                 gamemode: "FFA".to_owned(),
                 players: 7,
@@ -63,12 +366,87 @@ impl ServersState {
 
         app.watch_task(tokio::task::spawn(Self::server_fetch_task(app.clone())))
             .await;
+
+        if app.config.ping_concurrency != 0 {
+            app.watch_task(tokio::task::spawn(Self::server_ping_task(app.clone())))
+                .await;
+        }
     }
 
     pub fn count(&self) -> usize {
         self.items.len()
     }
 
+    pub async fn export_records(app: &AppState) -> Vec<ServerRecord> {
+        // cloned to not hold server lock together with others
+        let servers = app.servers.read().await.items.clone();
+        let versions = app.versions.read().await;
+        #[cfg(feature = "geolocation")]
+        let locations = app.locations.read().await;
+
+        servers
+            .into_iter()
+            .map(|s| ServerRecord {
+                #[cfg(feature = "geolocation")]
+                location: locations
+                    .items
+                    .get(&s.address.ip)
+                    .map(|l| format!("{}/{}", l.country, l.city)),
+                #[cfg(not(feature = "geolocation"))]
+                location: None,
+                installed: matches!(
+                    versions.items.get(&s.version),
+                    Some(Installation {
+                        kind: InstallationKind::Installed { .. },
+                        ..
+                    })
+                ),
+                address: s.address.to_string(),
+                ping_ms: s.ping.map(|p| p.smoothed.as_millis() as u64),
+                name: s.name,
+                players: s.players,
+                fork: s.version.fork,
+                build: s.version.build,
+                map: s.map,
+                gamemode: s.gamemode,
+                time: s.time,
+                fps: s.fps,
+                offline: s.offline,
+            })
+            .collect()
+    }
+
+    // writes timestamped export file to exports directory
+    pub async fn export_to_file(app: Arc<AppState>, format: ExportFormat) -> TaskResult {
+        let records = Self::export_records(&app).await;
+
+        let path = app.config.dirs.exports_dir.join(format!(
+            "servers-{}.{}",
+            chrono::Local::now().format("%Y%m%d-%H%M%S"),
+            format.extension()
+        ));
+
+        fs::write(&path, format.export(&records)?)
+            .await
+            .with_context(|| format!("Unable to write {}", path.display()))?;
+
+        app.events
+            .read()
+            .await
+            .event(&format!(
+                "Exported {} servers to {}",
+                records.len(),
+                path.display()
+            ))
+            .await;
+
+        Ok(())
+    }
+
+    pub fn filtered(&self, pattern: &str) -> Vec<&Server> {
+        self.items.iter().filter(|s| s.matches(pattern)).collect()
+    }
+
     pub async fn update(&mut self, app: Arc<AppState>, data: ServerListJson) {
         let mut previously_online: HashMap<Address, &mut Server> = self
             .items
@@ -84,7 +462,7 @@ impl ServersState {
                 ip: ip.clone(),
                 port: sv.port,
             };
-            let version = GameVersion::from(sv.clone());
+            let version = GameVersion::new(sv.clone(), &app.config.trusted_downloads);
 
             if let Some(known_server) = previously_online.remove(&address) {
                 // version changed (download/build/fork)
@@ -112,21 +490,35 @@ impl ServersState {
 
         self.items.append(&mut created_servers);
 
-        // TODO: pinned servers
-        // TODO: custom sorts by each field
-        // TODO: search by pattern
-        // sorting priorities:
-        //  - server is online
-        //  - player count
-        //  - server name
-        // https://stackoverflow.com/a/40369685
-        self.items.sort_by(|a, b| match a.offline.cmp(&b.offline) {
-            Ordering::Equal => match a.players.cmp(&b.players).reverse() {
-                Ordering::Equal => a.name.cmp(&b.name),
-                other => other,
-            },
-            other => other,
-        });
+        if let Err(err) = self.update_pins().await {
+            app.events.read().await.error(err).await;
+        }
+
+        self.sort();
+
+        if let Err(err) = self.history.record(&self.items).await {
+            app.events.read().await.error(err).await;
+        }
+
+        self.updated.notify_one();
+    }
+
+    // single server list update
+    pub async fn fetch(app: Arc<AppState>) -> anyhow::Result<()> {
+        let data = app
+            .client
+            .get(app.config.server_list_url.clone())
+            .send()
+            .await
+            .with_context(|| "sending server list request")?
+            .error_for_status()?
+            .json::<ServerListJson>()
+            .await
+            .with_context(|| "parsing server list response")?;
+
+        app.servers.write().await.update(app.clone(), data).await;
+
+        Ok(())
     }
 
     async fn server_fetch_task(app: Arc<AppState>) -> TaskResult {
@@ -135,29 +527,201 @@ impl ServersState {
         #[cfg(feature = "geolocation")]
         app.locations.write().await.resolve(&IP::Local).await;
 
-        async fn loop_body(app: Arc<AppState>) -> anyhow::Result<()> {
-            let data = app
-                .client
-                .get(SERVER_LIST_URL)
-                .send()
-                .await
-                .with_context(|| "sending server list request")?
-                .error_for_status()?
-                .json::<ServerListJson>()
-                .await
-                .with_context(|| "parsing server list response")?;
+        loop {
+            match Self::fetch(Arc::clone(&app)).await {
+                // not done in fetch, headless commands must not notify
+                Ok(()) => app.servers.write().await.check_watches(&app).await,
+                Err(err) => app.events.read().await.error(err).await,
+            }
+
+            tokio::time::sleep(update_interval).await;
+        }
+    }
+
+    // measures TCP handshake time with game port
+    pub async fn ping(address: &Address, timeout: Duration) -> anyhow::Result<Duration> {
+        let start = Instant::now();
 
-            app.servers.write().await.update(app.clone(), data).await;
+        tokio::time::timeout(timeout, TcpStream::connect(address.to_string()))
+            .await
+            .with_context(|| format!("{} timed out", address))?
+            .with_context(|| format!("connecting to {}", address))?;
 
-            Ok(())
+        Ok(start.elapsed())
+    }
+
+    fn set_ping(&mut self, address: &Address, rtt: Option<Duration>) {
+        if let Some(server) = self.items.iter_mut().find(|s| &s.address == address) {
+            server.ping = rtt.map(|rtt| Ping::new(server.ping, rtt));
         }
+    }
+
+    async fn server_ping_task(app: Arc<AppState>) -> TaskResult {
+        let updated = app.servers.read().await.updated.clone();
+        let limit = Arc::new(Semaphore::new(app.config.ping_concurrency));
 
         loop {
-            if let Err(err) = loop_body(Arc::clone(&app)).await {
-                app.events.read().await.error(err).await;
+            updated.notified().await;
+
+            let addresses: Vec<Address> = app
+                .servers
+                .read()
+                .await
+                .items
+                .iter()
+                .filter(|s| !s.offline && matches!(s.address.ip, IP::Remote(_)))
+                .map(|s| s.address.clone())
+                .collect();
+
+            let mut tasks = Vec::with_capacity(addresses.len());
+
+            for address in addresses {
+                let app = Arc::clone(&app);
+                let limit = Arc::clone(&limit);
+
+                tasks.push(tokio::spawn(async move {
+                    let _permit = limit.acquire().await.expect("semaphore closed");
+
+                    let rtt = match Self::ping(&address, Duration::from_secs(2)).await {
+                        Ok(rtt) => Some(rtt),
+                        Err(err) => {
+                            log::debug!("ping failed: {:#}", err);
+                            None
+                        }
+                    };
+
+                    app.servers.write().await.set_ping(&address, rtt);
+                }));
             }
 
-            tokio::time::sleep(update_interval).await;
+            for task in tasks {
+                task.await?;
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tokio::net::TcpListener;
+
+    use crate::datatypes::server::ServerJson;
+
+    fn local(port: u16) -> Address {
+        Address {
+            ip: IP::Remote("127.0.0.1".to_owned()),
+            port: port.into(),
+        }
+    }
+
+    fn online(name: &str, port: u16) -> Server {
+        Server::new(
+            local(port),
+            GameVersion {
+                fork: "UnityStationDevelop".to_owned(),
+                build: "1".to_owned(),
+                download: DownloadUrl::Local,
+            },
+            ServerJson {
+                name: name.to_owned(),
+                ..Default::default()
+            },
+        )
+    }
+
+    async fn pinned_state(name: &str, pinned: &[Server], online: Vec<Server>) -> ServersState {
+        let data_dir = std::env::temp_dir().join(format!("rshub-{}-{}", name, std::process::id()));
+        std::fs::remove_dir_all(&data_dir).ok();
+
+        let mut state = ServersState::new(&AppConfig::for_tests(&data_dir)).await;
+        state.settings.pinned = pinned.iter().map(PinnedServer::from).collect();
+        state.items = online;
+
+        state
+    }
+
+    fn pinned_ports(state: &ServersState) -> Vec<u32> {
+        state.settings.pinned.iter().map(|p| p.port).collect()
+    }
+
+    #[tokio::test]
+    async fn pin_follows_moved_server() {
+        let mut state = pinned_state(
+            "pin-moved",
+            &[online("Box", 7777)],
+            vec![online("Box", 7778)],
+        )
+        .await;
+
+        state.update_pins().await.unwrap();
+        assert_eq!(pinned_ports(&state), [7778]);
+
+        std::fs::remove_dir_all(state.settings_path.parent().unwrap()).ok();
+    }
+
+    #[tokio::test]
+    async fn pin_not_moved_to_ambiguous_name() {
+        let mut state = pinned_state(
+            "pin-ambiguous",
+            &[online("Box", 7777)],
+            vec![online("Box", 7778), online("Box", 7779)],
+        )
+        .await;
+
+        state.update_pins().await.unwrap();
+        assert_eq!(pinned_ports(&state), [7777]);
+
+        std::fs::remove_dir_all(state.settings_path.parent().unwrap()).ok();
+    }
+
+    #[tokio::test]
+    async fn pin_not_moved_to_pinned_address() {
+        let mut state = pinned_state(
+            "pin-duplicate",
+            &[online("Box", 7777), online("Box", 7778)],
+            vec![online("Box", 7778)],
+        )
+        .await;
+
+        state.update_pins().await.unwrap();
+        assert_eq!(pinned_ports(&state), [7777, 7778]);
+
+        std::fs::remove_dir_all(state.settings_path.parent().unwrap()).ok();
+    }
+
+    #[tokio::test]
+    async fn ping_local_listener() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let rtt = ServersState::ping(&local(port), Duration::from_secs(5)).await;
+
+        assert!(rtt.is_ok(), "{:?}", rtt);
+    }
+
+    #[tokio::test]
+    async fn ping_refused() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        assert!(ServersState::ping(&local(port), Duration::from_secs(5))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn ping_timeout() {
+        // TEST-NET-1, nothing answers there
+        let address = Address {
+            ip: IP::Remote("192.0.2.1".to_owned()),
+            port: 7777,
+        };
+
+        assert!(ServersState::ping(&address, Duration::from_millis(100))
+            .await
+            .is_err());
+    }
+}
@@ -1,46 +1,418 @@
-use std::path::PathBuf;
-use std::process::Stdio;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, bail, Context};
 
+use chrono::Utc;
+
+use serde::{Deserialize, Serialize};
+
 use futures::stream::StreamExt;
 
+use sha2::{Digest, Sha256};
+
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
-use tokio::process::Command;
-use tokio::sync::RwLock;
+use tokio::sync::Notify;
 
 use crate::config::AppConfig;
 use crate::datatypes::{
+    file_list::{FileList, Verification},
     game_version::{DownloadUrl, GameVersion},
-    installation::{Installation, InstallationKind},
+    installation::{
+        Installation, InstallationKind, Manifest, PartialDownload, PinnedBuild,
+        ARCHIVE_CHECKSUM_FILE,
+    },
+    launch_profile::{BuildProfile, ProfileSource},
+    rate_limiter::RateLimiter,
     server::Address,
     value_sorted_map::ValueSortedMap,
 };
 use crate::states::app::{AppState, TaskResult};
+use crate::states::games::GamesState;
+use crate::states::lock::StateLock;
+
+// version list preferences preserved between launches
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct VersionsSettings {
+    pinned: Vec<PinnedBuild>,
+    // launch profiles selected for builds, override fork and default profiles
+    profiles: Vec<BuildProfile>,
+}
 
 pub struct VersionsState {
     pub items: ValueSortedMap<GameVersion, Installation>,
-    pub install_dir_error: Option<String>,
+    // versions waiting for download, in order. paused entries are skipped
+    queue: VecDeque<GameVersion>,
+    active_downloads: usize,
+    max_downloads: usize,
+    // wakes up queue task
+    queue_changed: Arc<Notify>,
+    // notifies waiters after every finished (or failed) installation
+    installation_finished: Arc<Notify>,
+    // shared by all downloads
+    pub rate_limiter: Arc<RateLimiter>,
+    settings: VersionsSettings,
+    settings_path: PathBuf,
 }
 
+// how often download speed is recalculated
+const SPEED_SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
 impl VersionsState {
-    pub async fn new(_: &AppConfig) -> Self {
+    pub async fn new(config: &AppConfig) -> Self {
+        let settings_path = config.dirs.data_dir.join("versions.json");
+
         Self {
             items: ValueSortedMap::new(),
-            install_dir_error: None,
+            queue: VecDeque::new(),
+            active_downloads: 0,
+            max_downloads: config.max_downloads,
+            queue_changed: Arc::new(Notify::new()),
+            installation_finished: Arc::new(Notify::new()),
+            rate_limiter: Arc::new(RateLimiter::new(config.max_download_rate)),
+            settings: Self::load_settings(&settings_path).await,
+            settings_path,
         }
     }
 
     pub async fn run(&mut self, app: Arc<AppState>) {
+        Self::cleanup_staging(
+            &app.config.dirs.staging_dir,
+            &app.config.dirs.installations_dir,
+        )
+        .await;
+
         self.spawn_installation_finder(app.clone()).await;
+
+        app.watch_task(tokio::task::spawn(Self::download_queue_task(app.clone())))
+            .await;
+    }
+
+    // installation search is finished when this returns
+    pub async fn run_headless(app: Arc<AppState>) -> TaskResult {
+        Self::fs_installation_finder_task(app.config.clone(), app.versions.clone()).await?;
+
+        app.watch_task(tokio::task::spawn(Self::download_queue_task(app.clone())))
+            .await;
+
+        Ok(())
     }
 
     pub fn count(&self) -> usize {
         self.items.len()
     }
 
+    async fn load_settings(path: &Path) -> VersionsSettings {
+        let data = match fs::read(path).await {
+            Ok(data) => data,
+            Err(err) => {
+                log::debug!("unable to read {}: {}", path.display(), err);
+                return VersionsSettings::default();
+            }
+        };
+
+        serde_json::from_slice(&data).unwrap_or_else(|err| {
+            log::error!("bad version settings file {}: {}", path.display(), err);
+            VersionsSettings::default()
+        })
+    }
+
+    async fn save_settings(&self) -> anyhow::Result<()> {
+        fs::write(&self.settings_path, serde_json::to_vec(&self.settings)?)
+            .await
+            .with_context(|| format!("Unable to save {}", self.settings_path.display()))
+    }
+
+    pub fn is_pinned(&self, version: &GameVersion) -> bool {
+        self.settings.pinned.iter().any(|p| p.matches(version))
+    }
+
+    pub async fn toggle_pin(&mut self, version: &GameVersion) -> anyhow::Result<()> {
+        let before = self.settings.pinned.len();
+
+        self.settings.pinned.retain(|p| !p.matches(version));
+
+        if self.settings.pinned.len() == before {
+            self.settings.pinned.push(PinnedBuild::from(version));
+        }
+
+        self.save_settings().await
+    }
+
+    fn mark_broken(&mut self, version: &GameVersion, reason: String) {
+        let version = self
+            .items
+            .get(version)
+            .map_or_else(|| version.clone(), |i| i.version.clone());
+
+        self.items.insert(
+            version.clone(),
+            Installation {
+                version,
+                kind: InstallationKind::Broken { reason },
+            },
+        );
+    }
+
+    // profile applied on launch with where it was selected. unknown names are ignored, profiles
+    // could be removed from config
+    pub fn launch_profile<'a>(
+        &'a self,
+        config: &'a AppConfig,
+        version: &GameVersion,
+    ) -> Option<(&'a str, ProfileSource)> {
+        self.settings
+            .profiles
+            .iter()
+            .find(|p| p.matches(version))
+            .map(|p| (p.profile.as_str(), ProfileSource::Build))
+            .into_iter()
+            .chain(
+                config
+                    .fork_profiles
+                    .get(&version.fork)
+                    .map(|p| (p.as_str(), ProfileSource::Fork)),
+            )
+            .chain(
+                config
+                    .default_profile
+                    .as_deref()
+                    .map(|p| (p, ProfileSource::Default)),
+            )
+            .find(|(name, _)| config.profiles.contains_key(*name))
+    }
+
+    // switches build to next profile from config, after last one build profile is removed.
+    // returns selected profile
+    pub async fn cycle_profile(
+        &mut self,
+        config: &AppConfig,
+        version: &GameVersion,
+    ) -> anyhow::Result<Option<String>> {
+        if config.profiles.is_empty() {
+            bail!("No launch profiles in config");
+        }
+
+        let current = self
+            .settings
+            .profiles
+            .iter()
+            .position(|p| p.matches(version))
+            .map(|i| self.settings.profiles.remove(i).profile);
+
+        let next = match current {
+            Some(current) => config
+                .profiles
+                .range::<String, _>((
+                    std::ops::Bound::Excluded(&current),
+                    std::ops::Bound::Unbounded,
+                ))
+                .next(),
+            None => config.profiles.iter().next(),
+        }
+        .map(|(name, _)| name.clone());
+
+        if let Some(profile) = &next {
+            self.settings.profiles.push(BuildProfile {
+                fork: version.fork.clone(),
+                build: version.build.clone(),
+                profile: profile.clone(),
+            });
+        }
+
+        self.save_settings().await?;
+
+        Ok(next)
+    }
+
+    // installed builds not covered by retention policy, with their sizes. keeps newest builds
+    // of every fork, builds used by servers in list and pinned builds
+    pub fn cleanup_candidates(&self, used: &[GameVersion], keep: usize) -> Vec<(GameVersion, u64)> {
+        let mut forks: HashMap<&str, Vec<(&GameVersion, u64)>> = HashMap::new();
+
+        for installation in self.items.iter() {
+            if let InstallationKind::Installed { size, .. } = installation.kind {
+                forks
+                    .entry(&installation.version.fork)
+                    .or_default()
+                    .push((&installation.version, size));
+            }
+        }
+
+        let mut candidates = Vec::new();
+
+        for builds in forks.values_mut() {
+            // newest first. builds are usually numbers, but there is no guarantee
+            builds.sort_by_cached_key(|(v, _)| {
+                std::cmp::Reverse((v.build.parse::<u64>().ok(), v.build.clone()))
+            });
+
+            for (version, size) in builds.iter().skip(keep) {
+                if used.contains(version) || self.is_pinned(version) {
+                    continue;
+                }
+
+                candidates.push(((*version).clone(), *size));
+            }
+        }
+
+        candidates.sort_by(|a, b| a.0.cmp(&b.0));
+
+        candidates
+    }
+
+    // exclude is kept regardless of policy
+    pub async fn cleanup(app: Arc<AppState>, exclude: Option<GameVersion>) -> TaskResult {
+        // not holding both locks at once, server list update locks them in reverse order
+        let mut used: Vec<GameVersion> = app
+            .servers
+            .read()
+            .await
+            .items
+            .iter()
+            .map(|s| s.version.clone())
+            .collect();
+
+        used.extend(exclude);
+
+        let candidates = app
+            .versions
+            .read()
+            .await
+            .cleanup_candidates(&used, app.config.keep_builds);
+
+        Self::remove_builds(app, candidates).await
+    }
+
+    // removes builds selected by cleanup_candidates
+    pub async fn remove_builds(
+        app: Arc<AppState>,
+        candidates: Vec<(GameVersion, u64)>,
+    ) -> TaskResult {
+        if candidates.is_empty() {
+            app.events.read().await.event("Nothing to clean up").await;
+
+            return Ok(());
+        }
+
+        let mut freed = 0;
+        let mut removed = 0;
+
+        for (version, size) in candidates {
+            match Self::uninstall(app.clone(), version).await {
+                Ok(()) => {
+                    freed += size;
+                    removed += 1;
+                }
+                Err(err) => app.events.read().await.error(err).await,
+            }
+        }
+
+        app.events
+            .read()
+            .await
+            .event(&format!(
+                "Cleanup removed {} builds, freed {}",
+                removed,
+                bytesize::ByteSize::b(freed)
+            ))
+            .await;
+
+        Ok(())
+    }
+
+    // leftovers of installations interrupted by crash or kill. directories of running
+    // processes are kept, they might be extracting right now
+    async fn cleanup_staging(staging_dir: &Path, installations_dir: &Path) {
+        let mut dirs = match fs::read_dir(staging_dir).await {
+            Ok(dirs) => dirs,
+            Err(err) => {
+                log::error!("Unable to read staging directory: {}", err);
+                return;
+            }
+        };
+
+        while let Ok(Some(entry)) = dirs.next_entry().await {
+            let path = entry.path();
+
+            // entries that are not pid were made by older versions
+            let owner = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.parse::<u32>().ok());
+
+            if let Some(pid) = owner {
+                if pid == std::process::id() || process_alive(pid) {
+                    log::debug!("staging directory {} is in use", path.display());
+                    continue;
+                }
+
+                Self::restore_replaced(&path, installations_dir).await;
+            }
+
+            log::info!("removing leftover staging directory {}", path.display());
+
+            let result = if path.is_dir() {
+                fs::remove_dir_all(&path).await
+            } else {
+                fs::remove_file(&path).await
+            };
+
+            if let Err(err) = result {
+                log::error!("Unable to remove {}: {}", path.display(), err);
+            }
+        }
+    }
+
+    // build moved aside by stage is put back if process died before new one was moved in
+    async fn restore_replaced(owner_dir: &Path, installations_dir: &Path) {
+        let mut forks = match fs::read_dir(owner_dir).await {
+            Ok(forks) => forks,
+            Err(_) => return,
+        };
+
+        while let Ok(Some(fork)) = forks.next_entry().await {
+            let mut builds = match fs::read_dir(fork.path()).await {
+                Ok(builds) => builds,
+                Err(_) => continue,
+            };
+
+            while let Ok(Some(build)) = builds.next_entry().await {
+                let name = build.file_name();
+                let build_name = match name.to_str().and_then(|n| n.strip_suffix(".old")) {
+                    Some(build_name) => build_name,
+                    None => continue,
+                };
+
+                let target = installations_dir.join(fork.file_name()).join(build_name);
+
+                // new build was moved in, old one can be removed
+                if target.exists() {
+                    continue;
+                }
+
+                log::warn!("restoring interrupted replacement of {}", target.display());
+
+                let result = async {
+                    if let Some(fork_dir) = target.parent() {
+                        fs::create_dir_all(fork_dir).await?;
+                    }
+
+                    fs::rename(build.path(), &target).await
+                }
+                .await;
+
+                if let Err(err) = result {
+                    log::error!("Unable to restore {}: {}", target.display(), err);
+                }
+            }
+        }
+    }
+
     pub async fn spawn_installation_finder(&mut self, app: Arc<AppState>) {
         app.watch_task(tokio::task::spawn(Self::fs_installation_finder_task(
             app.config.clone(),
@@ -51,7 +423,7 @@ impl VersionsState {
 
     async fn fs_installation_finder_task(
         app: AppConfig,
-        versions: Arc<RwLock<Self>>,
+        versions: Arc<StateLock<Self>>,
     ) -> TaskResult {
         log::debug!(
             "installation directory: {}",
@@ -90,7 +462,7 @@ impl VersionsState {
                     continue;
                 }
 
-                let installation =
+                let mut installation =
                     Installation::try_from_dir(&build_path)
                         .await
                         .with_context(|| {
@@ -100,17 +472,15 @@ impl VersionsState {
                 log::info!("found installation: {:?}", &installation);
 
                 if let Some(existing) = versions.items.get(&installation.version.clone()) {
-                    if matches!(
-                        existing,
-                        Installation {
-                            kind: InstallationKind::Downloading { .. }
-                                | InstallationKind::Unpacking,
-                            ..
-                        }
-                    ) {
+                    if existing.kind.is_pending() {
                         log::warn!("not overriding existing version {:?}", existing);
                         continue;
                     }
+
+                    // broken build can be reinstalled if download is known
+                    if matches!(installation.kind, InstallationKind::Broken { .. }) {
+                        installation.version.download = existing.version.download.clone();
+                    }
                 }
 
                 versions
@@ -125,15 +495,7 @@ impl VersionsState {
     // intentionally blocking
     pub async fn refresh(&mut self, app: Arc<AppState>) {
         // remove everything except installing versions
-        self.items.retain(|i| {
-            matches!(
-                i,
-                Installation {
-                    kind: InstallationKind::Downloading { .. } | InstallationKind::Unpacking,
-                    ..
-                }
-            )
-        });
+        self.items.retain(|i| i.kind.is_pending());
 
         // grab versions from servers state
         for server in &app.servers.read().await.items {
@@ -165,10 +527,27 @@ impl VersionsState {
         let mut versions = app.versions.write().await;
 
         if let Some(existing) = versions.items.get(version).cloned() {
-            if !matches!(&existing.kind, InstallationKind::Discovered) {
-                log::debug!("not replacing existing {:?} with discovered", existing);
+            match existing.kind {
+                InstallationKind::Discovered => {}
+                // found in file system, download is needed for reinstall
+                InstallationKind::Broken { .. }
+                    if matches!(existing.version.download, DownloadUrl::Local) =>
+                {
+                    versions.items.insert(
+                        version.clone(),
+                        Installation {
+                            version: version.clone(),
+                            kind: existing.kind,
+                        },
+                    );
 
-                return;
+                    return;
+                }
+                _ => {
+                    log::debug!("not replacing existing {:?} with discovered", existing);
+
+                    return;
+                }
             }
         }
 
@@ -187,220 +566,1115 @@ impl VersionsState {
         );
     }
 
-    pub async fn install(app: Arc<AppState>, version: GameVersion) -> TaskResult {
-        let url = match &version.download {
-            DownloadUrl::Valid(url) => url,
-            DownloadUrl::Untrusted(url) => {
-                if !app.config.unchecked_downloads {
-                    bail!("Not downloading (untrusted URL): `{}`", url);
-                }
-
-                url
-            }
-            DownloadUrl::Invalid(bad) => {
-                bail!("Not downloading (invalid URL): `{}`", bad);
-            }
-            DownloadUrl::Local => {
-                bail!("Attempted to download installed version");
-            }
-        }
-        .to_owned();
+    pub async fn enqueue(app: Arc<AppState>, version: GameVersion, first: bool) -> TaskResult {
+        // fail early instead of waiting for queue
+        Self::download_url(&app, &version)?;
 
-        app.events
-            .read()
-            .await
-            .event(&format!("Downloading {}", version))
-            .await;
+        let mut versions = app.versions.write().await;
 
-        match app.versions.read().await.items.get(&version) {
+        match versions.items.get(&version) {
             Some(Installation {
-                kind: InstallationKind::Discovered,
+                kind: InstallationKind::Discovered | InstallationKind::Broken { .. },
                 ..
             }) => {}
+            Some(Installation {
+                kind: InstallationKind::Queued { .. },
+                ..
+            }) if first => {
+                versions.queue.retain(|v| v != &version);
+            }
+            Some(Installation { kind, .. }) if kind.is_pending() => {
+                return Ok(());
+            }
             Some(_) => {
                 bail!("Attempted to download installed version");
             }
-            _ => {
+            None => {
                 bail!("state desync: not found, ignoring install request");
             }
         }
 
-        let versions = app.versions.clone();
-
-        let response = app
-            .client
-            .get(url.clone())
-            .send()
-            .await
-            .with_context(|| "Initial request failed")?;
-
-        let total = response.content_length();
-
-        let build_home = app
-            .config
-            .dirs
-            .installations_dir
-            .join(PathBuf::from(version.clone()));
+        versions.items.insert(
+            version.clone(),
+            Installation {
+                version: version.clone(),
+                kind: InstallationKind::Queued { paused: false },
+            },
+        );
 
-        let archive_file = build_home.join("data.zip");
+        if first {
+            versions.queue.push_front(version.clone());
+        } else {
+            versions.queue.push_back(version.clone());
+        }
 
-        fs::create_dir_all(&build_home)
-            .await
-            .with_context(|| "Unable to create installation folder")?;
+        versions.queue_changed.notify_one();
 
-        log::warn!("{}", archive_file.display());
-        let mut file = fs::File::create(archive_file.clone())
+        app.events
+            .read()
             .await
-            .with_context(|| "Unable to create archive file")?;
-
-        let mut stream = response.bytes_stream();
+            .event(&format!("Queued {}", version))
+            .await;
 
-        let mut progress = 0;
+        Ok(())
+    }
 
-        versions.write().await.items.insert(
-            version.clone(),
-            Installation {
-                version: version.clone(),
-                kind: InstallationKind::Downloading { progress: 0, total },
-            },
-        );
+    pub fn queue_position(&self, version: &GameVersion) -> Option<usize> {
+        self.queue.iter().position(|v| v == version)
+    }
 
-        while let Some(item) = stream.next().await {
-            let chunk = match item {
-                Ok(chunk) => chunk,
-                Err(err) => {
-                    bail!("Failed to read next chunk: {}", err);
-                }
+    // moves queued version 1 position closer to start or end
+    pub fn move_in_queue(&mut self, version: &GameVersion, up: bool) {
+        if let Some(i) = self.queue_position(version) {
+            let other = if up {
+                i.checked_sub(1)
+            } else {
+                Some(i + 1).filter(|&j| j < self.queue.len())
             };
 
-            if let Err(err) = file.write(&chunk).await {
-                bail!("Failed to write next chunk: {}", err);
+            if let Some(j) = other {
+                self.queue.swap(i, j);
             }
+        }
+    }
 
-            progress += chunk.len();
+    pub fn toggle_pause(&mut self, version: &GameVersion) {
+        if let Some(Installation {
+            kind: InstallationKind::Queued { paused },
+            ..
+        }) = self.items.get(version)
+        {
+            let paused = !paused;
 
-            let mut versions = versions.write().await;
-            let previous = versions.items.insert(
+            self.items.insert(
                 version.clone(),
                 Installation {
                     version: version.clone(),
-                    kind: InstallationKind::Downloading {
-                        progress: progress as u64,
-                        total,
-                    },
+                    kind: InstallationKind::Queued { paused },
                 },
             );
 
-            if !matches!(
-                previous,
-                Some(Installation {
-                    kind: InstallationKind::Downloading { .. },
-                    ..
-                }),
-            ) {
-                log::info!("aborting installation because installation state changed");
+            self.queue_changed.notify_one();
+        }
+    }
 
-                previous.and_then(|previous| versions.items.insert(version.clone(), previous));
+    // starts queued installations while there are free download slots
+    async fn download_queue_task(app: Arc<AppState>) -> TaskResult {
+        let queue_changed = app.versions.read().await.queue_changed.clone();
 
-                if let Err(err) = fs::remove_dir_all(&build_home).await {
-                    log::error!(
-                        "Unable to cleanup download directory {}: {}",
-                        build_home.display(),
-                        err
-                    );
-                }
+        loop {
+            queue_changed.notified().await;
 
-                return Ok(());
-            }
-        }
+            let mut versions = app.versions.write().await;
 
-        versions.write().await.items.insert(
-            version.clone(),
-            Installation {
-                version: version.clone(),
-                kind: InstallationKind::Unpacking,
-            },
-        );
+            while versions.active_downloads < versions.max_downloads {
+                let next = versions.queue.iter().position(|v| {
+                    matches!(
+                        versions.items.get(v),
+                        Some(Installation {
+                            kind: InstallationKind::Queued { paused: false },
+                            ..
+                        })
+                    )
+                });
 
-        drop(file);
+                let version = match next.and_then(|i| versions.queue.remove(i)) {
+                    Some(version) => version,
+                    None => break,
+                };
 
-        let path_cloned = archive_file.clone();
-        let path_parent_cloned = build_home.clone();
+                versions.active_downloads += 1;
 
-        app.events
-            .read()
-            .await
-            .event(&format!("Extracting {}", version))
-            .await;
+                let app_cloned = Arc::clone(&app);
 
-        tokio::task::spawn_blocking(move || -> TaskResult {
-            zip::read::ZipArchive::new(
-                std::fs::File::open(path_cloned.clone())
-                    .with_context(|| "Unable to read zip file")?,
-            )
-            .with_context(|| "Unable to decode zip file")?
-            .extract(path_parent_cloned)
-            .with_context(|| "Unable to extract zip file")
-        })
-        .await
-        .with_context(|| "Task joining failed")?
-        .with_context(|| "Archive decompression failed")?;
+                app.watch_task(tokio::spawn(async move {
+                    let result = Self::install(Arc::clone(&app_cloned), version).await;
 
-        if let Err(err) = fs::remove_file(&archive_file).await {
-            log::error!(
-                "Unable to cleanup zip file {}: {}",
-                archive_file.display(),
-                err
-            );
+                    let mut versions = app_cloned.versions.write().await;
+                    versions.active_downloads -= 1;
+                    versions.queue_changed.notify_one();
+                    versions.installation_finished.notify_waiters();
+
+                    result
+                }))
+                .await;
+            }
         }
+    }
 
-        versions.write().await.items.insert(
-            version.clone(),
-            Installation {
-                version: version.clone(),
-                kind: InstallationKind::Installed {
-                    size: Installation::get_folder_size(&build_home)
-                        .await
-                        .unwrap_or_default(),
+    // queues version first if it is not installed and waits until it is
+    pub async fn wait_for_installation(app: Arc<AppState>, version: GameVersion) -> TaskResult {
+        let installation_finished = app.versions.read().await.installation_finished.clone();
+
+        let mut queued = false;
+
+        loop {
+            // created before state check to not miss notification
+            let finished = installation_finished.notified();
+
+            let installation = app
+                .versions
+                .read()
+                .await
+                .items
+                .get(&version)
+                .ok_or_else(|| anyhow!("desync: version not in installation list"))?
+                .clone();
+
+            match installation.kind {
+                InstallationKind::Installed { .. } => return Ok(()),
+                // not in server list, there is nowhere to reinstall it from
+                InstallationKind::Broken { reason }
+                    if matches!(installation.version.download, DownloadUrl::Local) =>
+                {
+                    bail!("{} is broken: {}", version, reason);
+                }
+                // also resumes and moves to front if already queued
+                InstallationKind::Discovered
+                | InstallationKind::Broken { .. }
+                | InstallationKind::Queued { .. }
+                    if !queued =>
+                {
+                    Self::enqueue(app.clone(), installation.version, true).await?;
+                    queued = true;
+                }
+                InstallationKind::Queued { .. }
+                | InstallationKind::Downloading { .. }
+                | InstallationKind::Unpacking => {}
+                InstallationKind::Discovered => {
+                    bail!("Installation of {} failed or was aborted", version);
+                }
+                InstallationKind::Broken { reason } => {
+                    bail!("{} is broken: {}", version, reason);
+                }
+            }
+
+            finished.await;
+        }
+    }
+
+    fn download_url(app: &AppState, version: &GameVersion) -> anyhow::Result<reqwest::Url> {
+        Ok(match &version.download {
+            DownloadUrl::Valid(url) => url,
+            DownloadUrl::Untrusted(url) => {
+                if !app.config.unchecked_downloads {
+                    bail!("Not downloading (untrusted URL): `{}`", url);
+                }
+
+                url
+            }
+            DownloadUrl::Invalid(bad) => {
+                bail!("Not downloading (invalid URL): `{}`", bad);
+            }
+            DownloadUrl::Local => {
+                bail!("Attempted to download installed version");
+            }
+        }
+        .to_owned())
+    }
+
+    async fn install(app: Arc<AppState>, version: GameVersion) -> TaskResult {
+        let url = Self::download_url(&app, &version)?;
+
+        match app.versions.read().await.items.get(&version) {
+            Some(Installation {
+                kind: InstallationKind::Queued { .. },
+                ..
+            }) => {}
+            // aborted while waiting in queue
+            Some(Installation {
+                kind: InstallationKind::Discovered,
+                ..
+            }) => return Ok(()),
+            Some(_) => {
+                bail!("Attempted to download installed version");
+            }
+            _ => {
+                bail!("state desync: not found, ignoring install request");
+            }
+        }
+
+        app.events
+            .read()
+            .await
+            .event(&format!("Downloading {}", version))
+            .await;
+
+        let result = Self::download_and_extract(Arc::clone(&app), version.clone(), url).await;
+
+        if let Err(err) = &result {
+            let mut versions = app.versions.write().await;
+
+            match versions.items.get(&version).map(|i| &i.kind) {
+                // return to initial state to allow retrying
+                Some(InstallationKind::Downloading { .. }) => {
+                    versions.items.insert(
+                        version.clone(),
+                        Installation {
+                            version: version.clone(),
+                            kind: InstallationKind::Discovered,
+                        },
+                    );
+                }
+                // archive is bad, can still be retried
+                Some(InstallationKind::Unpacking) => {
+                    versions.mark_broken(&version, format!("{:#}", err));
+                }
+                _ => {}
+            }
+        } else if app.config.auto_cleanup {
+            // freshly installed version might be old and not used by anyone, but it is wanted
+            Self::cleanup(app.clone(), Some(version)).await?;
+        }
+
+        result
+    }
+
+    async fn download_and_extract(
+        app: Arc<AppState>,
+        version: GameVersion,
+        url: reqwest::Url,
+    ) -> TaskResult {
+        let versions = app.versions.clone();
+
+        let archive_file = PartialDownload::archive_path(&app.config.dirs.downloads_dir, &version);
+        let metadata_file =
+            PartialDownload::metadata_path(&app.config.dirs.downloads_dir, &version);
+
+        if let Some(fork_dir) = archive_file.parent() {
+            fs::create_dir_all(fork_dir)
+                .await
+                .with_context(|| "Unable to create download folder")?;
+        }
+
+        let (response, offset) =
+            Self::request_download(&app, &url, &archive_file, &metadata_file).await?;
+
+        let total = response.content_length().map(|length| length + offset);
+
+        let mut partial = PartialDownload {
+            url: url.to_string(),
+            etag: header_string(&response, reqwest::header::ETAG),
+            last_modified: header_string(&response, reqwest::header::LAST_MODIFIED),
+            written: offset,
+        };
+
+        partial.save(&metadata_file).await?;
+
+        let mut file = if offset == 0 {
+            fs::File::create(&archive_file).await
+        } else {
+            fs::OpenOptions::new()
+                .append(true)
+                .open(&archive_file)
+                .await
+        }
+        .with_context(|| "Unable to open archive file")?;
+
+        // hash of already downloaded part
+        let mut hasher = if offset == 0 {
+            Sha256::new()
+        } else {
+            let archive_file = archive_file.clone();
+
+            tokio::task::spawn_blocking(move || -> anyhow::Result<Sha256> {
+                let mut hasher = Sha256::new();
+                std::io::copy(&mut std::fs::File::open(archive_file)?, &mut hasher)?;
+
+                Ok(hasher)
+            })
+            .await
+            .with_context(|| "Task joining failed")?
+            .with_context(|| "Unable to hash partial download")?
+        };
+
+        let expected_checksum = Self::fetch_checksum(&app, &url).await;
+
+        let mut stream = response.bytes_stream();
+
+        let rate_limiter = versions.read().await.rate_limiter.clone();
+
+        versions.write().await.items.insert(
+            version.clone(),
+            Installation {
+                version: version.clone(),
+                kind: InstallationKind::Downloading {
+                    progress: offset,
+                    total,
+                    speed: None,
                 },
             },
-        );
+        );
+
+        let mut speed: Option<u64> = None;
+        let mut sample_start = Instant::now();
+        let mut sample_bytes = 0;
+
+        // Ok(false) means download was aborted
+        let result: anyhow::Result<bool> = async {
+            while let Some(item) = stream.next().await {
+                let chunk = item.with_context(|| "Failed to read next chunk")?;
+
+                rate_limiter.acquire(chunk.len() as u64).await;
+
+                file.write_all(&chunk)
+                    .await
+                    .with_context(|| "Failed to write next chunk")?;
+
+                hasher.update(&chunk);
+                partial.written += chunk.len() as u64;
+
+                sample_bytes += chunk.len() as u64;
+
+                let elapsed = sample_start.elapsed();
+                if elapsed >= SPEED_SAMPLE_INTERVAL {
+                    let current = (sample_bytes as f64 / elapsed.as_secs_f64()) as u64;
+
+                    // smooth out spikes
+                    speed = Some(speed.map_or(current, |previous| (previous + current) / 2));
+
+                    sample_start = Instant::now();
+                    sample_bytes = 0;
+                }
+
+                let mut versions = versions.write().await;
+                let previous = versions.items.insert(
+                    version.clone(),
+                    Installation {
+                        version: version.clone(),
+                        kind: InstallationKind::Downloading {
+                            progress: partial.written,
+                            total,
+                            speed,
+                        },
+                    },
+                );
+
+                if !matches!(
+                    previous,
+                    Some(Installation {
+                        kind: InstallationKind::Downloading { .. },
+                        ..
+                    }),
+                ) {
+                    log::info!("aborting installation because installation state changed");
+
+                    previous.and_then(|previous| versions.items.insert(version.clone(), previous));
+
+                    return Ok(false);
+                }
+            }
+
+            Ok(true)
+        }
+        .await;
+
+        file.flush()
+            .await
+            .with_context(|| "Unable to write archive file")?;
+        drop(file);
+
+        match result {
+            Ok(true) => {}
+            // keep partial file to resume later
+            Ok(false) | Err(_) => {
+                partial.save(&metadata_file).await?;
+
+                return result.map(|_| ());
+            }
+        }
+
+        let checksum = format!("{:x}", hasher.finalize());
+
+        if let Some(expected) = expected_checksum {
+            if expected != checksum {
+                // file is broken, there is no point in resuming it
+                for path in [&archive_file, &metadata_file] {
+                    if let Err(err) = fs::remove_file(path).await {
+                        log::error!("Unable to remove {}: {}", path.display(), err);
+                    }
+                }
+
+                bail!(
+                    "Checksum mismatch for {}: expected {}, got {}",
+                    version,
+                    expected,
+                    checksum
+                );
+            }
+
+            log::info!("checksum verified: {}", checksum);
+        } else {
+            log::info!("no published checksum for {}, got {}", url, checksum);
+        }
+
+        if let Err(err) = fs::remove_file(&metadata_file).await {
+            log::error!(
+                "Unable to cleanup partial download metadata {}: {}",
+                metadata_file.display(),
+                err
+            );
+        }
+
+        versions.write().await.items.insert(
+            version.clone(),
+            Installation {
+                version: version.clone(),
+                kind: InstallationKind::Unpacking,
+            },
+        );
+
+        let build_home = app
+            .config
+            .dirs
+            .installations_dir
+            .join(PathBuf::from(version.clone()));
+
+        let staging = Installation::staging_path(&app.config.dirs.staging_dir, &version);
+
+        app.events
+            .read()
+            .await
+            .event(&format!("Extracting {}", version))
+            .await;
+
+        let manifest = Manifest {
+            url: url.to_string(),
+            installed_at: Utc::now(),
+            sha256: checksum,
+            server: app
+                .servers
+                .read()
+                .await
+                .items
+                .iter()
+                .find(|s| s.version == version)
+                .map(|s| s.name.clone()),
+            last_launched: None,
+        };
+
+        let replaced = match Self::stage(&archive_file, &staging, &build_home, &manifest).await {
+            Ok(replaced) => replaced,
+            Err(err) => {
+                // rollback: nothing is left in installations directory
+                if staging.exists() {
+                    if let Err(err) = fs::remove_dir_all(&staging).await {
+                        log::error!("Unable to remove {}: {}", staging.display(), err);
+                    }
+                }
+
+                return Err(err);
+            }
+        };
+
+        if let Err(err) = fs::remove_file(&archive_file).await {
+            log::error!(
+                "Unable to cleanup zip file {}: {}",
+                archive_file.display(),
+                err
+            );
+        }
+
+        let size = Installation::get_folder_size(&build_home)
+            .await
+            .unwrap_or_default();
+
+        // checked under same lock as insert, abort can not be missed
+        let aborted = {
+            let mut versions = versions.write().await;
+
+            if matches!(
+                versions.items.get(&version).map(|i| &i.kind),
+                Some(InstallationKind::Unpacking)
+            ) {
+                versions.items.insert(
+                    version.clone(),
+                    Installation {
+                        version: version.clone(),
+                        kind: InstallationKind::Installed {
+                            size,
+                            manifest: Some(manifest),
+                        },
+                    },
+                );
+
+                false
+            } else {
+                true
+            }
+        };
+
+        if aborted {
+            log::info!("aborting installation because installation state changed");
+
+            Self::unstage(&versions, &version, &build_home, replaced).await;
+
+            return Ok(());
+        }
+
+        if let Some(old) = replaced {
+            if let Err(err) = fs::remove_dir_all(&old).await {
+                log::error!("Unable to remove {}: {}", old.display(), err);
+            }
+        }
+
+        app.events
+            .read()
+            .await
+            .event(&format!("Installed version {}", version))
+            .await;
+
+        Ok(())
+    }
+
+    // extracts archive into staging directory, validates it and moves it into place. returns
+    // replaced build moved aside, it is removed by caller
+    async fn stage(
+        archive_file: &Path,
+        staging: &Path,
+        build_home: &Path,
+        manifest: &Manifest,
+    ) -> anyhow::Result<Option<PathBuf>> {
+        if staging.exists() {
+            fs::remove_dir_all(staging)
+                .await
+                .with_context(|| "Unable to clear staging folder")?;
+        }
+
+        fs::create_dir_all(staging)
+            .await
+            .with_context(|| "Unable to create staging folder")?;
+
+        let path_cloned = archive_file.to_owned();
+        let staging_cloned = staging.to_owned();
+
+        let file_list = tokio::task::spawn_blocking(move || -> anyhow::Result<FileList> {
+            Installation::extract(&path_cloned, &staging_cloned)
+                .with_context(|| "Archive decompression failed")?;
+
+            FileList::hash_dir(&staging_cloned).with_context(|| "Unable to record file list")
+        })
+        .await
+        .with_context(|| "Task joining failed")??;
+
+        Installation::make_executable(staging).with_context(|| "Invalid build")?;
+
+        log::info!("recorded {} files", file_list.len());
+
+        file_list.save(staging).await?;
+
+        // sha256sum compatible format
+        fs::write(
+            staging.join(ARCHIVE_CHECKSUM_FILE),
+            format!("{}  {}\n", manifest.sha256, manifest.url),
+        )
+        .await
+        .with_context(|| "Unable to save archive checksum")?;
+
+        manifest.save(staging).await?;
+
+        if let Some(fork_dir) = build_home.parent() {
+            fs::create_dir_all(fork_dir)
+                .await
+                .with_context(|| "Unable to create installation folder")?;
+        }
+
+        // leftover of interrupted installation made by older versions or build being repaired.
+        // moved aside into staging directory and removed after new build is in place, it is
+        // restored on next startup if process dies between renames
+        let replaced = if build_home.exists() {
+            log::warn!("replacing existing directory {}", build_home.display());
+
+            let mut old_name = staging.file_name().unwrap_or_default().to_owned();
+            old_name.push(".old");
+            let old = staging.with_file_name(old_name);
+
+            if old.exists() {
+                fs::remove_dir_all(&old)
+                    .await
+                    .with_context(|| "Unable to clear replaced installation folder")?;
+            }
+
+            fs::rename(build_home, &old)
+                .await
+                .with_context(|| "Unable to move existing installation folder")?;
+
+            Some(old)
+        } else {
+            None
+        };
+
+        // staging and installations are on the same filesystem, so each rename is atomic
+        if let Err(err) = fs::rename(staging, build_home).await {
+            if let Some(old) = &replaced {
+                if let Err(err) = fs::rename(old, build_home).await {
+                    log::error!("Unable to restore {}: {}", build_home.display(), err);
+                }
+            }
+
+            return Err(err).with_context(|| "Unable to move build into installation folder");
+        }
+
+        Ok(replaced)
+    }
+
+    // undoes stage after abort: new build is removed and replaced one is put back
+    async fn unstage(
+        versions: &StateLock<Self>,
+        version: &GameVersion,
+        build_home: &Path,
+        replaced: Option<PathBuf>,
+    ) {
+        if let Err(err) = fs::remove_dir_all(build_home).await {
+            log::error!("Unable to remove {}: {}", build_home.display(), err);
+            return;
+        }
+
+        let old = match replaced {
+            Some(old) => old,
+            None => return,
+        };
+
+        if let Err(err) = fs::rename(&old, build_home).await {
+            log::error!("Unable to restore {}: {}", build_home.display(), err);
+            return;
+        }
+
+        // state was reset by abort, it is known again from restored directory
+        if let Ok(mut installation) = Installation::try_from_dir(build_home).await {
+            let mut versions = versions.write().await;
+
+            if matches!(
+                versions.items.get(version).map(|i| &i.kind),
+                Some(InstallationKind::Discovered) | None
+            ) {
+                installation.version.download = version.download.clone();
+                versions.items.insert(version.clone(), installation);
+            }
+        }
+    }
+
+    // published checksum is optional, looks for sha256sum compatible <archive url>.sha256
+    async fn fetch_checksum(app: &AppState, url: &reqwest::Url) -> Option<String> {
+        // next to archive, query is kept
+        let mut checksum_url = url.clone();
+        checksum_url.set_path(&format!("{}.sha256", url.path()));
+
+        let response = match app.client.get(checksum_url.clone()).send().await {
+            Ok(response) if response.status().is_success() => response,
+            Ok(response) => {
+                log::debug!("no checksum at {}: {}", checksum_url, response.status());
+                return None;
+            }
+            Err(err) => {
+                log::warn!("unable to fetch checksum {}: {}", checksum_url, err);
+                return None;
+            }
+        };
+
+        let text = response.text().await.ok()?;
+        let checksum = text.split_whitespace().next()?.to_lowercase();
+
+        if checksum.len() == 64 && checksum.chars().all(|c| c.is_ascii_hexdigit()) {
+            Some(checksum)
+        } else {
+            log::warn!("bad checksum file {}", checksum_url);
+            None
+        }
+    }
+
+    // tries to continue previous download if there is one. returns response and offset
+    // response body starts at
+    async fn request_download(
+        app: &AppState,
+        url: &reqwest::Url,
+        archive_file: &Path,
+        metadata_file: &Path,
+    ) -> anyhow::Result<(reqwest::Response, u64)> {
+        let existing_size = fs::metadata(archive_file)
+            .await
+            .map(|m| m.len())
+            .unwrap_or_default();
+
+        let resumable = match PartialDownload::load(metadata_file).await {
+            Some(partial) if partial.url == url.as_str() && existing_size != 0 => {
+                partial.validator().map(str::to_owned)
+            }
+            _ => None,
+        };
+
+        if let Some(validator) = resumable {
+            log::info!("resuming download of {} from {}", url, existing_size);
+
+            let response = app
+                .client
+                .get(url.clone())
+                .header(reqwest::header::RANGE, format!("bytes={}-", existing_size))
+                // server sends full file if it changed
+                .header(reqwest::header::IF_RANGE, validator)
+                .send()
+                .await
+                .with_context(|| "Initial request failed")?;
+
+            let range_start =
+                header_string(&response, reqwest::header::CONTENT_RANGE).and_then(|range| {
+                    range
+                        .strip_prefix("bytes ")?
+                        .split('-')
+                        .next()?
+                        .parse::<u64>()
+                        .ok()
+                });
+
+            match response.status() {
+                reqwest::StatusCode::PARTIAL_CONTENT if range_start == Some(existing_size) => {
+                    return Ok((response, existing_size));
+                }
+                reqwest::StatusCode::OK => {
+                    log::info!("server sent full file, restarting download");
+
+                    return Ok((response, 0));
+                }
+                status => {
+                    log::info!("unable to resume download ({}), restarting", status);
+                }
+            }
+        }
+
+        let response = app
+            .client
+            .get(url.clone())
+            .send()
+            .await
+            .with_context(|| "Initial request failed")?
+            .error_for_status()?;
+
+        Ok((response, 0))
+    }
+
+    // lines for confirmation dialog, None if there is nothing to uninstall
+    pub fn describe_uninstall(
+        &self,
+        config: &AppConfig,
+        version: &GameVersion,
+    ) -> Option<Vec<String>> {
+        match self.items.get(version) {
+            Some(Installation {
+                kind: InstallationKind::Installed { size, .. },
+                ..
+            }) => Some(vec![
+                format!(
+                    "Removes {}",
+                    config
+                        .dirs
+                        .installations_dir
+                        .join(PathBuf::from(version.clone()))
+                        .display()
+                ),
+                format!("Frees {}", bytesize::ByteSize::b(*size)),
+            ]),
+            Some(Installation {
+                kind: InstallationKind::Broken { .. },
+                ..
+            }) => Some(vec![format!(
+                "Removes {}",
+                config
+                    .dirs
+                    .installations_dir
+                    .join(PathBuf::from(version.clone()))
+                    .display()
+            )]),
+            _ => None,
+        }
+    }
+
+    // lines for confirmation dialog, None if there is nothing to repair
+    pub fn describe_repair(
+        &self,
+        config: &AppConfig,
+        version: &GameVersion,
+    ) -> Option<Vec<String>> {
+        match self.items.get(version).map(|i| &i.kind) {
+            Some(InstallationKind::Installed { .. } | InstallationKind::Broken { .. }) => {
+                Some(vec![
+                    "Verifies files, version is downloaded again only if they are damaged"
+                        .to_owned(),
+                    format!(
+                        "Replaces {}",
+                        config
+                            .dirs
+                            .installations_dir
+                            .join(PathBuf::from(version.clone()))
+                            .display()
+                    ),
+                ])
+            }
+            _ => None,
+        }
+    }
+
+    // title and lines for confirmation dialog, None if abort does not lose anything
+    pub async fn describe_abort(
+        &self,
+        config: &AppConfig,
+        version: &GameVersion,
+    ) -> Option<(String, Vec<String>)> {
+        match self.items.get(version).map(|i| &i.kind) {
+            Some(InstallationKind::Downloading { progress, .. }) => Some((
+                format!("Abort installation of {}?", version),
+                vec![format!(
+                    "Downloaded {} is kept, download can be resumed later",
+                    bytesize::ByteSize::b(*progress)
+                )],
+            )),
+            Some(InstallationKind::Unpacking) => Some((
+                format!("Abort installation of {}?", version),
+                vec!["Download is finished, extracted build is going to be discarded".to_owned()],
+            )),
+            Some(InstallationKind::Queued { .. }) => None,
+            _ => {
+                let archive_file =
+                    PartialDownload::archive_path(&config.dirs.downloads_dir, version);
+                let size = fs::metadata(&archive_file).await.ok()?.len();
+
+                Some((
+                    format!("Discard partial download of {}?", version),
+                    vec![
+                        format!("Removes {}", archive_file.display()),
+                        format!("Frees {}", bytesize::ByteSize::b(size)),
+                    ],
+                ))
+            }
+        }
+    }
+
+    pub async fn abort_installation(app: Arc<AppState>, version: GameVersion) -> TaskResult {
+        let mut versions = app.versions.write().await;
+
+        if matches!(
+            versions.items.get(&version),
+            Some(Installation { kind, .. }) if kind.is_pending()
+        ) {
+            versions.queue.retain(|v| v != &version);
+            versions.installation_finished.notify_waiters();
+
+            versions.items.insert(
+                version.clone(),
+                Installation {
+                    version: version.clone(),
+                    kind: InstallationKind::Discovered,
+                },
+            );
+
+            app.events
+                .read()
+                .await
+                .event(&format!("Aborted installation of {}", version))
+                .await;
+
+            return Ok(());
+        }
+
+        // second abort discards downloaded data
+        let archive_file = PartialDownload::archive_path(&app.config.dirs.downloads_dir, &version);
+        let metadata_file =
+            PartialDownload::metadata_path(&app.config.dirs.downloads_dir, &version);
+
+        if !archive_file.exists() {
+            bail!("Nothing to abort");
+        }
+
+        fs::remove_file(&archive_file)
+            .await
+            .with_context(|| "Unable to remove partial download")?;
+
+        if let Err(err) = fs::remove_file(&metadata_file).await {
+            log::warn!(
+                "Unable to remove partial download metadata {}: {}",
+                metadata_file.display(),
+                err
+            );
+        }
+
+        app.events
+            .read()
+            .await
+            .event(&format!("Discarded partial download of {}", version))
+            .await;
+
+        Ok(())
+    }
+
+    // hashes installed files and compares them with list recorded during installation
+    pub async fn verify(app: &AppState, version: &GameVersion) -> anyhow::Result<Verification> {
+        match app
+            .versions
+            .read()
+            .await
+            .items
+            .get(version)
+            .map(|i| &i.kind)
+        {
+            Some(InstallationKind::Installed { .. } | InstallationKind::Broken { .. }) => {}
+            _ => bail!("{} is not installed", version),
+        }
+
+        let path = app
+            .config
+            .dirs
+            .installations_dir
+            .join(PathBuf::from(version.clone()));
+
+        let file_list = FileList::load(&path).await?.ok_or_else(|| {
+            anyhow!(
+                "No file list recorded for {}, it was installed by older version of rshub",
+                version
+            )
+        })?;
+
+        let current = tokio::task::spawn_blocking(move || FileList::hash_dir(&path))
+            .await
+            .with_context(|| "Task joining failed")??;
+
+        Ok(file_list.compare(&current))
+    }
+
+    // verification result goes to event log
+    pub async fn verify_task(app: Arc<AppState>, version: GameVersion) -> TaskResult {
+        app.events
+            .read()
+            .await
+            .event(&format!("Verifying {}", version))
+            .await;
+
+        let verification = Self::verify(&app, &version).await?;
+
+        for line in verification.details() {
+            log::info!("{}: {}", version, line);
+        }
+
+        if verification.is_intact() {
+            app.events
+                .read()
+                .await
+                .event(&format!("Verified {}: {}", version, verification))
+                .await;
+
+            Ok(())
+        } else {
+            Err(
+                anyhow!("{}", verification.details().join(", ")).context(format!(
+                    "{} is damaged: {}, repair to reinstall it",
+                    version, verification
+                )),
+            )
+        }
+    }
+
+    // reinstalls version if verification fails, extra files are not a reason to do it
+    pub async fn repair(app: Arc<AppState>, version: GameVersion) -> TaskResult {
+        let installation = app
+            .versions
+            .read()
+            .await
+            .items
+            .get(&version)
+            .cloned()
+            .ok_or_else(|| anyhow!("{} is not installed", version))?;
+
+        let path = app
+            .config
+            .dirs
+            .installations_dir
+            .join(PathBuf::from(version.clone()));
+
+        let reason = match &installation.kind {
+            InstallationKind::Installed { .. } => match Self::verify(&app, &version).await {
+                Ok(verification) if verification.is_intact() => {
+                    app.events
+                        .read()
+                        .await
+                        .event(&format!(
+                            "{} does not need repair: {}",
+                            version, verification
+                        ))
+                        .await;
+
+                    return Ok(());
+                }
+                Ok(verification) => verification.to_string(),
+                // without file list damage can not be detected, reinstall records it
+                Err(err) => format!("{:#}", err),
+            },
+            InstallationKind::Broken { reason } => {
+                // files can be intact with only execute permission missing
+                let fixed = Installation::make_executable(&path).is_ok()
+                    && matches!(
+                        Self::verify(&app, &version).await,
+                        Ok(verification) if verification.is_intact()
+                    );
+
+                if fixed {
+                    let mut repaired = Installation::try_from_dir(&path).await?;
+                    repaired.version.download = installation.version.download.clone();
+
+                    app.versions
+                        .write()
+                        .await
+                        .items
+                        .insert(version.clone(), repaired);
+
+                    app.events
+                        .read()
+                        .await
+                        .event(&format!("Repaired {}: made executable", version))
+                        .await;
+
+                    return Ok(());
+                }
+
+                reason.clone()
+            }
+            _ => bail!("{} is not installed", version),
+        };
+
+        // installations found in file system only know url from manifest
+        let download = match &installation.version.download {
+            DownloadUrl::Local => match Manifest::load(&path).await {
+                Some(manifest) => DownloadUrl::new(&manifest.url, &app.config.trusted_downloads),
+                None => bail!("Unable to repair {}: download url is unknown", version),
+            },
+            download => download.clone(),
+        };
 
         app.events
             .read()
             .await
-            .event(&format!("Installed version {}", version))
+            .event(&format!("Repairing {}: {}", version, reason))
             .await;
 
-        Ok(())
-    }
+        let repaired = GameVersion {
+            download,
+            ..version.clone()
+        };
 
-    pub async fn abort_installation(app: Arc<AppState>, version: GameVersion) -> TaskResult {
-        let mut versions = app.versions.write().await;
+        app.versions.write().await.items.insert(
+            repaired.clone(),
+            Installation {
+                version: repaired.clone(),
+                kind: InstallationKind::Broken { reason },
+            },
+        );
 
-        if matches!(
-            versions.items.get(&version),
-            Some(Installation {
-                kind: InstallationKind::Downloading { .. } | InstallationKind::Unpacking,
-                ..
-            })
-        ) {
-            versions.items.insert(
-                version.clone(),
-                Installation {
-                    version: version.clone(),
-                    kind: InstallationKind::Discovered,
-                },
-            );
+        Self::wait_for_installation(app.clone(), repaired)
+            .await
+            .with_context(|| format!("Unable to repair {}", version))?;
 
-            app.events
-                .read()
-                .await
-                .event(&format!("Aborted installation of {}", version))
-                .await;
-        } else {
-            bail!("Nothing to abort");
-        }
+        app.events
+            .read()
+            .await
+            .event(&format!("Repaired {}", version))
+            .await;
 
         Ok(())
     }
@@ -413,19 +1687,20 @@ impl VersionsState {
         // lock in advance
         let mut versions = app.versions.write().await;
 
-        match versions.items.get(&version) {
-            Some(Installation {
-                kind: InstallationKind::Installed { .. },
-                ..
-            }) => {}
+        let broken = match versions.items.get(&version).map(|i| &i.kind) {
+            Some(InstallationKind::Installed { .. }) => false,
+            Some(InstallationKind::Broken { .. }) => true,
             _ => {
                 bail!("not installed, nothing to remove: {}", version);
             }
-        }
+        };
 
-        fs::remove_dir_all(path)
-            .await
-            .with_context(|| "Unable to remove build directory")?;
+        // broken build might not have been extracted
+        if !broken || path.exists() {
+            fs::remove_dir_all(path)
+                .await
+                .with_context(|| "Unable to remove build directory")?;
+        }
 
         versions.items.remove(&version);
 
@@ -440,65 +1715,601 @@ impl VersionsState {
         Ok(())
     }
 
+    // updates last launch time in manifest, legacy installations are left alone
+    async fn record_launch(app: &AppState, version: &GameVersion, path: &Path) {
+        let mut versions = app.versions.write().await;
+
+        let (size, mut manifest) = match versions.items.get(version) {
+            Some(Installation {
+                kind:
+                    InstallationKind::Installed {
+                        size,
+                        manifest: Some(manifest),
+                    },
+                ..
+            }) => (*size, manifest.clone()),
+            _ => return,
+        };
+
+        manifest.last_launched = Some(Utc::now());
+
+        if let Err(err) = manifest.save(path).await {
+            log::error!("{:#}", err);
+        }
+
+        versions.items.insert(
+            version.clone(),
+            Installation {
+                version: version.clone(),
+                kind: InstallationKind::Installed {
+                    size,
+                    manifest: Some(manifest),
+                },
+            },
+        );
+    }
+
     pub async fn launch(
         app: Arc<AppState>,
         version: GameVersion,
         address: Option<Address>,
     ) -> TaskResult {
+        let profile = app
+            .versions
+            .read()
+            .await
+            .launch_profile(&app.config, &version)
+            .map(|(name, _)| name.to_owned());
+
         app.events
             .read()
             .await
-            .event(&format!("Launching {}", version))
+            .event(&match &profile {
+                Some(profile) => format!("Launching {} with profile {}", version, profile),
+                None => format!("Launching {}", version),
+            })
             .await;
 
-        if !matches!(
-            app.versions
-                .read()
-                .await
-                .items
-                .get(&version)
-                .ok_or_else(|| anyhow!("desync: version not in installation list"))?,
-            Installation {
-                kind: InstallationKind::Installed { .. },
-                ..
-            }
-        ) {
-            Self::install(app.clone(), version.clone())
-                .await
-                .with_context(|| "Unable to install")?;
-        }
+        Self::wait_for_installation(app.clone(), version.clone())
+            .await
+            .with_context(|| "Unable to install")?;
 
-        // https://github.com/unitystation/stationhub/blob/cebb9d45bff0a1c019852795a471068ba89d770a/UnitystationLauncher/Models/Installation.cs#L33-L104
         let path = app
             .config
             .dirs
             .installations_dir
             .join(PathBuf::from(version.clone()));
 
-        #[cfg(target_family = "unix")]
-        let exec_path = path.join("Unitystation");
-        #[cfg(target_os = "windows")]
-        let exec_path = path.join("Unitystation.exe");
-        #[cfg(not(any(target_family = "unix", target_os = "windows")))]
-        bail!("Unsupported OS");
+        // files could be removed or permissions changed since installation
+        let exec_path = match Installation::check_executable(&path) {
+            Ok(exec_path) => exec_path,
+            Err(err) => {
+                app.versions
+                    .write()
+                    .await
+                    .mark_broken(&version, format!("{:#}", err));
+
+                return Err(err.context(format!("{} is broken", version)));
+            }
+        };
+
+        Self::record_launch(&app, &version, &path).await;
+
+        let profile = profile
+            .and_then(|p| app.config.profiles.get(&p))
+            .cloned()
+            .unwrap_or_default();
 
-        let mut command = Command::new(&exec_path);
-        command.current_dir(&path);
+        let mut command = profile.command(&exec_path, &path);
 
-        if let Some(address) = address {
+        if let Some(address) = &address {
             command
                 .arg("--server")
                 .arg(address.ip.to_string())
                 .arg("--port")
                 .arg(address.port.to_string());
         }
-        command
-            .stdin(Stdio::null())
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .spawn()
-            .with_context(|| "Unable to launch installation")?;
 
-        Ok(())
+        command.args(&profile.args);
+
+        GamesState::spawn(app, command, version, address).await
+    }
+}
+
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+    // SAFETY: signal 0 only checks if process exists
+    if unsafe { libc::kill(pid as libc::pid_t, 0) } == 0 {
+        return true;
+    }
+
+    // exists, but belongs to other user
+    std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+// can not be checked without extra dependencies, leftovers are kept
+#[cfg(not(unix))]
+fn process_alive(_: u32) -> bool {
+    true
+}
+
+fn header_string(
+    response: &reqwest::Response,
+    name: reqwest::header::HeaderName,
+) -> Option<String> {
+    response
+        .headers()
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_owned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Write;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    const ETAG: &str = "\"v1\"";
+
+    type Handler = dyn Fn(&str) -> Vec<u8> + Send + Sync;
+
+    // answers every connection with single response, returns base url and received requests
+    async fn serve(handler: Box<Handler>) -> (reqwest::Url, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let received = requests.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+
+                let mut request = Vec::new();
+                let mut buf = [0; 1024];
+                while !request.ends_with(b"\r\n\r\n") {
+                    match stream.read(&mut buf).await.unwrap() {
+                        0 => break,
+                        n => request.extend_from_slice(&buf[..n]),
+                    }
+                }
+
+                let request = String::from_utf8_lossy(&request).into_owned();
+                let response = handler(&request);
+
+                received.lock().unwrap().push(request);
+
+                stream.write_all(&response).await.ok();
+            }
+        });
+
+        (reqwest::Url::parse(&url).unwrap(), requests)
+    }
+
+    fn response(status: &str, headers: &[(&str, String)], body: &[u8]) -> Vec<u8> {
+        let mut head = format!(
+            "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+            status,
+            body.len()
+        );
+        for (name, value) in headers {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        head.push_str("\r\n");
+
+        let mut response = head.into_bytes();
+        response.extend_from_slice(body);
+        response
+    }
+
+    fn header<'a>(request: &'a str, name: &str) -> Option<&'a str> {
+        request.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+
+            if key.eq_ignore_ascii_case(name) {
+                Some(value.trim())
+            } else {
+                None
+            }
+        })
+    }
+
+    fn is_checksum(request: &str) -> bool {
+        request
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .contains(".sha256")
+    }
+
+    // published for every archive, mixed up parts are not detected by zip reader
+    fn checksum(body: &[u8]) -> Vec<u8> {
+        let checksum = format!("{:x}  build.zip\n", Sha256::digest(body));
+
+        response("200 OK", &[], checksum.as_bytes())
+    }
+
+    fn archive(content: &[u8]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+
+        writer
+            .start_file(
+                "Unitystation",
+                zip::write::FileOptions::default()
+                    .compression_method(zip::CompressionMethod::Stored)
+                    .unix_permissions(0o755),
+            )
+            .unwrap();
+        writer.write_all(content).unwrap();
+
+        writer.finish().unwrap().into_inner()
+    }
+
+    struct Download {
+        app: Arc<AppState>,
+        version: GameVersion,
+        url: reqwest::Url,
+        archive_file: PathBuf,
+        metadata_file: PathBuf,
+    }
+
+    impl Download {
+        async fn new(name: &str, url: reqwest::Url) -> Self {
+            let data_dir =
+                std::env::temp_dir().join(format!("rshub-{}-{}", name, std::process::id()));
+            std::fs::remove_dir_all(&data_dir).ok();
+
+            let app = AppState::new_headless(AppConfig::for_tests(&data_dir))
+                .await
+                .unwrap();
+
+            let url = url.join("build.zip").unwrap();
+            let version = GameVersion {
+                fork: "Test".to_owned(),
+                build: "1".to_owned(),
+                download: DownloadUrl::Valid(url.clone()),
+            };
+
+            Self {
+                archive_file: PartialDownload::archive_path(
+                    &app.config.dirs.downloads_dir,
+                    &version,
+                ),
+                metadata_file: PartialDownload::metadata_path(
+                    &app.config.dirs.downloads_dir,
+                    &version,
+                ),
+                app,
+                version,
+                url,
+            }
+        }
+
+        // state left by interrupted download
+        async fn interrupted(&self, written: &[u8], etag: &str) {
+            fs::create_dir_all(self.archive_file.parent().unwrap())
+                .await
+                .unwrap();
+            fs::write(&self.archive_file, written).await.unwrap();
+
+            PartialDownload {
+                url: self.url.to_string(),
+                etag: Some(etag.to_owned()),
+                last_modified: None,
+                written: written.len() as u64,
+            }
+            .save(&self.metadata_file)
+            .await
+            .unwrap();
+        }
+
+        async fn run(&self) -> TaskResult {
+            VersionsState::download_and_extract(
+                self.app.clone(),
+                self.version.clone(),
+                self.url.clone(),
+            )
+            .await
+        }
+
+        async fn installed(&self) -> Vec<u8> {
+            assert!(!self.archive_file.exists());
+            assert!(!self.metadata_file.exists());
+
+            fs::read(
+                self.app
+                    .config
+                    .dirs
+                    .installations_dir
+                    .join("Test")
+                    .join("1")
+                    .join("Unitystation"),
+            )
+            .await
+            .unwrap()
+        }
+    }
+
+    impl Drop for Download {
+        fn drop(&mut self) {
+            std::fs::remove_dir_all(&self.app.config.dirs.data_dir).ok();
+        }
+    }
+
+    // pid of exited process
+    #[cfg(unix)]
+    fn dead_pid() -> u32 {
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        child.wait().unwrap();
+
+        child.id()
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn cleanup_staging_of_dead_processes() {
+        let data_dir = std::env::temp_dir().join(format!("rshub-staging-{}", std::process::id()));
+        std::fs::remove_dir_all(&data_dir).ok();
+
+        let staging_dir = data_dir.join("staging");
+        let installations_dir = data_dir.join("installations");
+
+        let dead = staging_dir.join(dead_pid().to_string()).join("Test");
+        let running = staging_dir
+            .join(std::process::id().to_string())
+            .join("Test");
+
+        // crashed between moving installed build aside and moving new one in
+        std::fs::create_dir_all(dead.join("1.old")).unwrap();
+        std::fs::write(dead.join("1.old").join("Unitystation"), b"old").unwrap();
+        // new build was moved in, only removal of old one is left
+        std::fs::create_dir_all(dead.join("2.old")).unwrap();
+        std::fs::create_dir_all(installations_dir.join("Test").join("2")).unwrap();
+        // half extracted
+        std::fs::create_dir_all(dead.join("3")).unwrap();
+        // extracting right now
+        std::fs::create_dir_all(running.join("4")).unwrap();
+
+        VersionsState::cleanup_staging(&staging_dir, &installations_dir).await;
+
+        assert_eq!(
+            std::fs::read(
+                installations_dir
+                    .join("Test")
+                    .join("1")
+                    .join("Unitystation")
+            )
+            .unwrap(),
+            b"old"
+        );
+        assert!(installations_dir.join("Test").join("2").exists());
+        assert!(!dead.exists());
+        assert!(running.join("4").exists());
+
+        std::fs::remove_dir_all(&data_dir).ok();
+    }
+
+    #[tokio::test]
+    async fn resume_partial_content() {
+        let content = b"resumed build".repeat(20);
+        let body = archive(&content);
+        let split = body.len() / 2;
+
+        let served = body.clone();
+        let (url, requests) = serve(Box::new(move |request| {
+            if is_checksum(request) {
+                return checksum(&served);
+            }
+
+            match (header(request, "range"), header(request, "if-range")) {
+                (Some(range), Some(ETAG)) if range == format!("bytes={}-", split) => response(
+                    "206 Partial Content",
+                    &[
+                        ("ETag", ETAG.to_owned()),
+                        (
+                            "Content-Range",
+                            format!("bytes {}-{}/{}", split, served.len() - 1, served.len()),
+                        ),
+                    ],
+                    &served[split..],
+                ),
+                _ => response("200 OK", &[("ETag", ETAG.to_owned())], &served),
+            }
+        }))
+        .await;
+
+        let download = Download::new("resume-partial", url).await;
+        download.interrupted(&body[..split], ETAG).await;
+
+        download.run().await.unwrap();
+
+        assert_eq!(download.installed().await, content);
+
+        let requests = requests.lock().unwrap();
+        let downloads: Vec<_> = requests.iter().filter(|r| !is_checksum(r)).collect();
+        assert_eq!(downloads.len(), 1);
+        assert!(header(downloads[0], "range").is_some());
+    }
+
+    #[tokio::test]
+    async fn resume_changed_file() {
+        let old_body = archive(&b"old build".repeat(20));
+        let content = b"new build".repeat(20);
+        let body = archive(&content);
+
+        let served = body.clone();
+        let (url, requests) = serve(Box::new(move |request| {
+            if is_checksum(request) {
+                return checksum(&served);
+            }
+
+            // supports ranges, but full file is sent because If-Range validator is stale
+            match (header(request, "range"), header(request, "if-range")) {
+                (Some(range), Some("\"v2\"")) => {
+                    let start: usize = range["bytes=".len()..range.len() - 1].parse().unwrap();
+
+                    response(
+                        "206 Partial Content",
+                        &[
+                            ("ETag", "\"v2\"".to_owned()),
+                            (
+                                "Content-Range",
+                                format!("bytes {}-{}/{}", start, served.len() - 1, served.len()),
+                            ),
+                        ],
+                        &served[start..],
+                    )
+                }
+                _ => response("200 OK", &[("ETag", "\"v2\"".to_owned())], &served),
+            }
+        }))
+        .await;
+
+        let download = Download::new("resume-changed", url).await;
+        download
+            .interrupted(&old_body[..old_body.len() / 2], ETAG)
+            .await;
+
+        download.run().await.unwrap();
+
+        assert_eq!(download.installed().await, content);
+
+        let requests = requests.lock().unwrap();
+        let downloads: Vec<_> = requests.iter().filter(|r| !is_checksum(r)).collect();
+        assert_eq!(downloads.len(), 1);
+        assert_eq!(header(downloads[0], "if-range"), Some(ETAG));
+    }
+
+    #[tokio::test]
+    async fn resume_unsupported() {
+        let content = b"full build".repeat(20);
+        let body = archive(&content);
+
+        // range headers are ignored
+        let served = body.clone();
+        let (url, _) = serve(Box::new(move |request| {
+            if is_checksum(request) {
+                return checksum(&served);
+            }
+
+            response("200 OK", &[("ETag", ETAG.to_owned())], &served)
+        }))
+        .await;
+
+        let download = Download::new("resume-unsupported", url).await;
+        download.interrupted(&body[..body.len() / 2], ETAG).await;
+
+        // full file would be appended to partial one if 200 was treated as resume
+        download.run().await.unwrap();
+
+        assert_eq!(download.installed().await, content);
+    }
+
+    #[tokio::test]
+    async fn resume_wrong_range() {
+        let content = b"build".repeat(20);
+        let body = archive(&content);
+        let split = body.len() / 2;
+
+        let served = body.clone();
+        let (url, requests) = serve(Box::new(move |request| {
+            if is_checksum(request) {
+                return checksum(&served);
+            }
+
+            match header(request, "range") {
+                // starts from beginning instead of requested offset
+                Some(_) => response(
+                    "206 Partial Content",
+                    &[
+                        ("ETag", ETAG.to_owned()),
+                        (
+                            "Content-Range",
+                            format!("bytes 0-{}/{}", served.len() - 1, served.len()),
+                        ),
+                    ],
+                    &served,
+                ),
+                None => response("200 OK", &[("ETag", ETAG.to_owned())], &served),
+            }
+        }))
+        .await;
+
+        let download = Download::new("resume-wrong-range", url).await;
+        download.interrupted(&body[..split], ETAG).await;
+
+        download.run().await.unwrap();
+
+        assert_eq!(download.installed().await, content);
+
+        let requests = requests.lock().unwrap();
+        let downloads: Vec<_> = requests.iter().filter(|r| !is_checksum(r)).collect();
+        assert_eq!(downloads.len(), 2);
+        assert!(header(downloads[1], "range").is_none());
+    }
+
+    #[tokio::test]
+    async fn interrupted_download_kept() {
+        let content = b"interrupted build".repeat(20);
+        let body = archive(&content);
+        let split = body.len() / 2;
+
+        let attempts = AtomicUsize::new(0);
+        let served = body.clone();
+        let (url, _) = serve(Box::new(move |request| {
+            if is_checksum(request) {
+                return checksum(&served);
+            }
+
+            match header(request, "range") {
+                Some(_) => response(
+                    "206 Partial Content",
+                    &[
+                        ("ETag", ETAG.to_owned()),
+                        (
+                            "Content-Range",
+                            format!("bytes {}-{}/{}", split, served.len() - 1, served.len()),
+                        ),
+                    ],
+                    &served[split..],
+                ),
+                // connection is closed in the middle of body on first attempt
+                None => {
+                    let mut full = response("200 OK", &[("ETag", ETAG.to_owned())], &served);
+
+                    if attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+                        full.truncate(full.len() - (served.len() - split));
+                    }
+
+                    full
+                }
+            }
+        }))
+        .await;
+
+        let download = Download::new("interrupted", url).await;
+
+        assert!(download.run().await.is_err());
+
+        assert_eq!(
+            fs::read(&download.archive_file).await.unwrap(),
+            &body[..split]
+        );
+
+        let partial = PartialDownload::load(&download.metadata_file)
+            .await
+            .unwrap();
+        assert_eq!(partial.url, download.url.as_str());
+        assert_eq!(partial.etag.as_deref(), Some(ETAG));
+        assert_eq!(partial.written, split as u64);
+
+        download.run().await.unwrap();
+
+        assert_eq!(download.installed().await, content);
     }
 }
@@ -0,0 +1,165 @@
+use std::io;
+use std::sync::Arc;
+
+use bytesize::ByteSize;
+
+use crossterm::event::KeyCode;
+
+use tui::{
+    backend::CrosstermBackend,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::Span,
+    widgets::{Block, Borders, Paragraph, Row, Table},
+    Frame,
+};
+
+use crate::app::AppAction;
+use crate::datatypes::game_version::GameVersion;
+use crate::input::UserInput;
+use crate::states::help::HotKey;
+use crate::states::{AppState, VersionsState};
+use crate::views::{AppView, Drawable, HotKeys, InputProcessor, Named};
+
+// dry run of cleanup: lists builds retention policy would remove
+pub struct Cleanup {
+    // last drawn list, exactly these builds are removed on confirmation
+    candidates: Vec<(GameVersion, u64)>,
+}
+
+impl Cleanup {
+    pub fn new() -> Self {
+        Self {
+            candidates: Vec::new(),
+        }
+    }
+}
+
+impl AppView for Cleanup {}
+
+impl Named for Cleanup {
+    fn name(&self) -> String {
+        "Cleanup Preview".to_owned()
+    }
+}
+
+impl HotKeys for Cleanup {
+    fn hotkeys(&self) -> Vec<HotKey> {
+        vec![
+            HotKey {
+                description: "Remove listed builds",
+                key: KeyCode::Enter,
+                modifiers: None,
+            },
+            HotKey {
+                description: "Close preview",
+                key: KeyCode::Esc,
+                modifiers: None,
+            },
+        ]
+    }
+}
+
+#[async_trait::async_trait]
+impl InputProcessor for Cleanup {
+    async fn on_input(&mut self, input: &UserInput, app: Arc<AppState>) -> Option<AppAction> {
+        match input {
+            UserInput::Enter => {
+                app.watch_task(tokio::spawn(VersionsState::remove_builds(
+                    app.clone(),
+                    self.candidates.clone(),
+                )))
+                .await;
+
+                Some(AppAction::CloseView)
+            }
+            UserInput::Back => Some(AppAction::CloseView),
+            _ => None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Drawable for Cleanup {
+    async fn draw(
+        &mut self,
+        f: &mut Frame<CrosstermBackend<io::Stdout>>,
+        area: Rect,
+        app: Arc<AppState>,
+    ) {
+        // same as VersionsState::cleanup, server and version locks are not held together
+        let used: Vec<GameVersion> = app
+            .servers
+            .read()
+            .await
+            .items
+            .iter()
+            .map(|s| s.version.clone())
+            .collect();
+
+        self.candidates = app
+            .versions
+            .read()
+            .await
+            .cleanup_candidates(&used, app.config.keep_builds);
+        let candidates = &self.candidates;
+
+        let total: u64 = candidates.iter().map(|(_, size)| size).sum();
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(3)])
+            .split(area);
+
+        let rows: Vec<Row> = candidates
+            .iter()
+            .map(|(version, size)| {
+                Row::new(vec![
+                    format!("{}-{}", version.fork, version.build),
+                    ByteSize::b(*size).to_string(),
+                ])
+            })
+            .collect();
+
+        let table = Table::new(rows)
+            .header(
+                Row::new(vec!["VERSION", "SIZE"]).style(
+                    Style::default()
+                        .fg(Color::Blue)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            )
+            .block(
+                Block::default()
+                    .title(format!(
+                        "keeping {} newest builds per fork, builds used by servers and pinned builds",
+                        app.config.keep_builds
+                    ))
+                    .title_alignment(Alignment::Center)
+                    .borders(Borders::ALL),
+            )
+            .widths(&[Constraint::Percentage(80), Constraint::Percentage(20)]);
+
+        f.render_widget(table, chunks[0]);
+
+        let summary = if candidates.is_empty() {
+            Span::raw("Nothing to clean up")
+        } else {
+            Span::styled(
+                format!(
+                    "Enter removes {} builds, freeing {}",
+                    candidates.len(),
+                    ByteSize::b(total)
+                ),
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            )
+        };
+
+        f.render_widget(
+            Paragraph::new(summary)
+                .alignment(Alignment::Center)
+                .block(Block::default().borders(Borders::ALL)),
+            chunks[1],
+        );
+    }
+}
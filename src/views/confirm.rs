@@ -0,0 +1,136 @@
+use std::io;
+use std::sync::Arc;
+
+use crossterm::event::KeyCode;
+
+use tui::{
+    backend::CrosstermBackend,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::app::AppAction;
+use crate::input::UserInput;
+use crate::states::help::HotKey;
+use crate::states::AppState;
+use crate::views::{AppView, Drawable, HotKeys, InputProcessor, Named};
+
+pub struct Confirm {}
+
+impl AppView for Confirm {}
+
+impl Named for Confirm {
+    fn name(&self) -> String {
+        "Confirmation".to_owned()
+    }
+}
+
+impl HotKeys for Confirm {
+    fn hotkeys(&self) -> Vec<HotKey> {
+        vec![
+            HotKey {
+                description: "Confirm",
+                key: KeyCode::Char('y'),
+                modifiers: None,
+            },
+            HotKey {
+                description: "Cancel",
+                key: KeyCode::Char('n'),
+                modifiers: None,
+            },
+        ]
+    }
+}
+
+#[async_trait::async_trait]
+impl InputProcessor for Confirm {
+    async fn on_input(&mut self, input: &UserInput, app: Arc<AppState>) -> Option<AppAction> {
+        match input {
+            UserInput::Enter | UserInput::Char('y' | 'Y') => {
+                match app.confirm.lock().unwrap().take() {
+                    Some(confirmation) => Some(AppAction::Confirmed(Box::new(confirmation.action))),
+                    None => Some(AppAction::CloseView),
+                }
+            }
+            UserInput::Back | UserInput::Char('n' | 'N') => {
+                app.confirm.lock().unwrap().take();
+
+                Some(AppAction::CloseView)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Drawable for Confirm {
+    async fn draw(
+        &mut self,
+        f: &mut Frame<CrosstermBackend<io::Stdout>>,
+        area: Rect,
+        app: Arc<AppState>,
+    ) {
+        let confirm = app.confirm.lock().unwrap();
+
+        let confirmation = match &confirm.pending {
+            Some(confirmation) => confirmation,
+            None => return,
+        };
+
+        let mut text: Vec<Spans> = confirmation
+            .details
+            .iter()
+            .map(|line| Spans::from(line.as_str()))
+            .collect();
+
+        text.push(Spans::from(""));
+        text.push(Spans::from(vec![
+            Span::styled("Enter/Y", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(": confirm, "),
+            Span::styled("Esc/N", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(": cancel"),
+        ]));
+
+        // + 2 for borders
+        let height = text.len() as u16 + 2;
+        let vertical_margin = area.height.saturating_sub(height) / 2;
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(vertical_margin),
+                Constraint::Length(height),
+                Constraint::Min(0),
+            ])
+            .split(area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(15),
+                Constraint::Percentage(70),
+                Constraint::Percentage(15),
+            ])
+            .split(chunks[1]);
+
+        f.render_widget(
+            Paragraph::new(text)
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: true })
+                .block(
+                    Block::default()
+                        .title(Span::styled(
+                            confirmation.title.clone(),
+                            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                        ))
+                        .title_alignment(Alignment::Center)
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(Color::Red)),
+                ),
+            chunks[1],
+        );
+    }
+}
@@ -0,0 +1,224 @@
+use std::io::{self, Write};
+use std::sync::Arc;
+
+use crossterm::event::KeyCode;
+
+use tui::{
+    backend::CrosstermBackend,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    symbols::DOT,
+    text::{Span, Spans},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::app::AppAction;
+use crate::input::UserInput;
+use crate::states::events::{EventRecord, Severity};
+use crate::states::help::HotKey;
+use crate::states::{AppState, StatelessList};
+use crate::views::{AppView, Drawable, HotKeys, InputProcessor, Named};
+
+// history of events and errors. oldest first to keep selection when new events arrive
+pub struct EventLog {
+    state: StatelessList<ListState>,
+    errors_only: bool,
+}
+
+impl EventLog {
+    pub fn new() -> Self {
+        Self {
+            state: StatelessList::new(ListState::default(), false),
+            errors_only: false,
+        }
+    }
+
+    async fn records(&self, app: &AppState) -> Vec<EventRecord> {
+        app.events.read().await.history(self.errors_only)
+    }
+}
+
+// OSC 52 escape sequence, supported by most terminal emulators and works over ssh
+fn copy_to_clipboard(text: &str) -> io::Result<()> {
+    let mut stdout = io::stdout();
+
+    write!(stdout, "\x1b]52;c;{}\x07", base64::encode(text))?;
+    stdout.flush()
+}
+
+impl AppView for EventLog {}
+
+impl Named for EventLog {
+    fn name(&self) -> String {
+        "Event Log".to_owned()
+    }
+}
+
+impl HotKeys for EventLog {
+    fn hotkeys(&self) -> Vec<HotKey> {
+        let mut hotkeys = vec![
+            HotKey {
+                description: "Close event log",
+                key: KeyCode::Esc,
+                modifiers: None,
+            },
+            HotKey {
+                description: "Show only errors or all events",
+                key: KeyCode::Char('e'),
+                modifiers: None,
+            },
+            HotKey {
+                description: "Copy selected event to clipboard",
+                key: KeyCode::Char('c'),
+                modifiers: None,
+            },
+            HotKey {
+                description: "Export shown events to data directory",
+                key: KeyCode::Char('x'),
+                modifiers: None,
+            },
+        ];
+
+        // Esc is used for closing
+        hotkeys.extend(
+            self.state
+                .hotkeys()
+                .into_iter()
+                .filter(|h| h.key != KeyCode::Esc),
+        );
+
+        hotkeys
+    }
+}
+
+#[async_trait::async_trait]
+impl InputProcessor for EventLog {
+    async fn on_input(&mut self, input: &UserInput, app: Arc<AppState>) -> Option<AppAction> {
+        match input {
+            UserInput::Back => Some(AppAction::CloseView),
+            UserInput::Char('e' | 'E') => {
+                self.errors_only = !self.errors_only;
+                self.state.unselect();
+
+                None
+            }
+            UserInput::Char('c' | 'C') => {
+                let records = self.records(&app).await;
+
+                if let Some(record) = self.state.selected().and_then(|i| records.get(i)) {
+                    let events = app.events.read().await;
+
+                    match copy_to_clipboard(&record.to_string()) {
+                        Ok(()) => events.event("Copied event to clipboard").await,
+                        Err(err) => events.error(err.into()).await,
+                    }
+                }
+
+                None
+            }
+            UserInput::Char('x' | 'X') => {
+                let events = app.events.read().await;
+
+                match events
+                    .export(&app.config.dirs.exports_dir, self.errors_only)
+                    .await
+                {
+                    Ok(path) => {
+                        events
+                            .event(&format!("Exported events to {}", path.display()))
+                            .await
+                    }
+                    Err(err) => events.error(err).await,
+                }
+
+                None
+            }
+            _ => {
+                let count = self.records(&app).await.len();
+
+                self.state.on_input(input, count)
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Drawable for EventLog {
+    async fn draw(
+        &mut self,
+        f: &mut Frame<CrosstermBackend<io::Stdout>>,
+        area: Rect,
+        app: Arc<AppState>,
+    ) {
+        let records = self.records(&app).await;
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(8)])
+            .split(area);
+
+        let items: Vec<ListItem> = records
+            .iter()
+            .map(|r| {
+                let style = match r.severity {
+                    Severity::Info => Style::default().fg(Color::Green),
+                    Severity::Error => Style::default().fg(Color::Red),
+                };
+
+                ListItem::new(Spans::from(vec![
+                    Span::raw(format!("{} ", r.time.format("%H:%M:%S"))),
+                    Span::styled(format!("{:5} ", r.severity), style),
+                    Span::raw(r.message.clone()),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(
+                        " EVENTS {} {} {} ",
+                        DOT,
+                        if self.errors_only { "errors" } else { "all" },
+                        records.len()
+                    ))
+                    .title_alignment(Alignment::Center),
+            )
+            .highlight_style(
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::BOLD),
+            );
+
+        f.render_stateful_widget(list, chunks[0], &mut self.state.state);
+
+        let details = match self.state.selected().and_then(|i| records.get(i)) {
+            Some(record) => {
+                let mut lines = vec![Spans::from(format!(
+                    "{} {}",
+                    record.time.format("%Y-%m-%d %H:%M:%S"),
+                    record.message
+                ))];
+
+                lines.extend(record.chain.iter().skip(1).map(|cause| {
+                    Spans::from(vec![
+                        Span::styled("caused by: ", Style::default().fg(Color::Red)),
+                        Span::raw(cause.clone()),
+                    ])
+                }));
+
+                lines
+            }
+            None => vec![Spans::from("select event")],
+        };
+
+        f.render_widget(
+            Paragraph::new(details)
+                .block(Block::default().borders(Borders::ALL).title("details"))
+                .wrap(Wrap { trim: true }),
+            chunks[1],
+        );
+    }
+}
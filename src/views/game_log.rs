@@ -0,0 +1,171 @@
+use std::io;
+use std::sync::Arc;
+
+use bytesize::ByteSize;
+
+use crossterm::event::KeyCode;
+
+use tui::{
+    backend::CrosstermBackend,
+    layout::{Alignment, Rect},
+    style::{Color, Style},
+    symbols::DOT,
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+use crate::app::AppAction;
+use crate::input::UserInput;
+use crate::states::games::{self, GameStatus};
+use crate::states::help::HotKey;
+use crate::states::AppState;
+use crate::views::{format_duration, AppView, Drawable, HotKeys, InputProcessor, Named};
+
+// only end of file is read, logs of long sessions can be large
+const TAIL_BYTES: u64 = 256 * 1024;
+
+// follows end of game log unless scrolled up
+pub struct GameLog {
+    // lines from the end
+    scroll: usize,
+}
+
+impl GameLog {
+    pub fn new() -> Self {
+        Self { scroll: 0 }
+    }
+}
+
+impl AppView for GameLog {}
+
+impl Named for GameLog {
+    fn name(&self) -> String {
+        "Game Log".to_owned()
+    }
+}
+
+impl HotKeys for GameLog {
+    fn hotkeys(&self) -> Vec<HotKey> {
+        vec![
+            HotKey {
+                description: "Close log",
+                key: KeyCode::Esc,
+                modifiers: None,
+            },
+            HotKey {
+                description: "Scroll up (scrollwheel support)",
+                key: KeyCode::Up,
+                modifiers: None,
+            },
+            HotKey {
+                description: "Scroll down (scrollwheel support)",
+                key: KeyCode::Down,
+                modifiers: None,
+            },
+            HotKey {
+                description: "Go to beginning of loaded log",
+                key: KeyCode::Home,
+                modifiers: None,
+            },
+            HotKey {
+                description: "Follow end of log",
+                key: KeyCode::End,
+                modifiers: None,
+            },
+        ]
+    }
+}
+
+#[async_trait::async_trait]
+impl InputProcessor for GameLog {
+    async fn on_input(&mut self, input: &UserInput, _: Arc<AppState>) -> Option<AppAction> {
+        match input {
+            UserInput::Back => {
+                self.scroll = 0;
+
+                return Some(AppAction::CloseView);
+            }
+            UserInput::Up => self.scroll += 1,
+            UserInput::Down => self.scroll = self.scroll.saturating_sub(1),
+            // clamped when drawing
+            UserInput::Top => self.scroll = usize::MAX,
+            UserInput::Bottom => self.scroll = 0,
+            _ => {}
+        }
+
+        None
+    }
+}
+
+#[async_trait::async_trait]
+impl Drawable for GameLog {
+    async fn draw(
+        &mut self,
+        f: &mut Frame<CrosstermBackend<io::Stdout>>,
+        area: Rect,
+        app: Arc<AppState>,
+    ) {
+        let games = app.games.read().await;
+
+        let path = match &games.viewed_log {
+            Some(path) => path,
+            None => return,
+        };
+
+        let mut title = vec![Span::raw(format!(" {} ", path.display()))];
+
+        if let Some(game) = games.by_log(path) {
+            let (status, color) = match game.status {
+                GameStatus::Running => ("running".to_owned(), Color::Green),
+                GameStatus::Exited(Some(0)) => ("exited".to_owned(), Color::Yellow),
+                GameStatus::Exited(Some(code)) => (format!("exited with {}", code), Color::Red),
+                GameStatus::Exited(None) => ("killed".to_owned(), Color::Red),
+            };
+
+            title.push(Span::raw(format!("{} ", DOT)));
+            title.push(Span::styled(status, Style::default().fg(color)));
+            title.push(Span::raw(format!(
+                " {} pid {} {} uptime {} {} {} ",
+                DOT,
+                game.pid.map_or_else(|| "?".to_owned(), |p| p.to_string()),
+                DOT,
+                format_duration(game.uptime().as_secs()),
+                DOT,
+                ByteSize::b(game.log_size)
+            )));
+
+            if let Some(address) = &game.address {
+                title.push(Span::raw(format!("{} {} ", DOT, address)));
+            }
+        }
+
+        let lines = match games::tail(path, TAIL_BYTES) {
+            Ok(lines) => lines,
+            Err(err) => vec![format!("unable to read log: {}", err)],
+        };
+
+        // - 2 for borders
+        let height = area.height.saturating_sub(2) as usize;
+
+        self.scroll = self.scroll.min(lines.len().saturating_sub(height));
+
+        let end = lines.len() - self.scroll;
+        let start = end.saturating_sub(height);
+
+        let text: Vec<Spans> = lines[start..end]
+            .iter()
+            .map(|line| Spans::from(line.as_str()))
+            .collect();
+
+        f.render_widget(
+            Paragraph::new(text).block(
+                Block::default()
+                    .title(Spans::from(title))
+                    .title_alignment(Alignment::Center)
+                    .borders(Borders::ALL),
+            ),
+            area,
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use std::io;
+use std::sync::Arc;
+
+use crossterm::event::KeyCode;
+
+use tui::{
+    backend::CrosstermBackend,
+    layout::{Alignment, Constraint, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Cell, Row, Table, TableState},
+    Frame,
+};
+
+use crate::app::AppAction;
+use crate::input::UserInput;
+use crate::states::games::{Game, GameStatus, StopSignal};
+use crate::states::help::HotKey;
+use crate::states::{AppState, StatelessList};
+use crate::views::{format_duration, Drawable, HotKeys, InputProcessor, Named, ViewType};
+
+pub struct GamesView {
+    state: StatelessList<TableState>,
+    // exited games are removed from list, index is not enough to keep selection
+    selected: Option<u64>,
+}
+
+impl GamesView {
+    pub fn new() -> Self {
+        Self {
+            state: StatelessList::new(TableState::default(), false),
+            selected: None,
+        }
+    }
+
+    // find previously selected game in current list
+    fn sync_selection(&mut self, games: &[Game]) {
+        match self
+            .selected
+            .and_then(|id| games.iter().position(|g| g.id == id))
+        {
+            Some(i) => self.state.select_index(i),
+            None => {
+                self.state.unselect();
+                self.selected = None;
+            }
+        }
+    }
+
+    async fn stop_selected(&mut self, app: &AppState, signal: StopSignal) {
+        let games = app.games.read().await;
+
+        self.sync_selection(&games.items);
+
+        if let Some(id) = self.selected {
+            if let Err(err) = games.stop(id, signal) {
+                app.events.read().await.error(err).await;
+            }
+        }
+    }
+}
+
+impl Named for GamesView {
+    fn name(&self) -> String {
+        "Running Games".to_owned()
+    }
+}
+
+impl HotKeys for GamesView {
+    fn hotkeys(&self) -> Vec<HotKey> {
+        let mut hotkeys = vec![
+            HotKey {
+                description: "Terminate selected game",
+                key: KeyCode::Char('t'),
+                modifiers: None,
+            },
+            HotKey {
+                description: "Kill selected game",
+                key: KeyCode::Char('k'),
+                modifiers: None,
+            },
+            HotKey {
+                description: "Open output log of selected game",
+                key: KeyCode::Char('o'),
+                modifiers: None,
+            },
+        ];
+
+        hotkeys.append(&mut self.state.hotkeys());
+
+        hotkeys
+    }
+}
+
+#[async_trait::async_trait]
+impl InputProcessor for GamesView {
+    async fn on_input(&mut self, input: &UserInput, app: Arc<AppState>) -> Option<AppAction> {
+        match input {
+            UserInput::Char('t' | 'T') => {
+                self.stop_selected(&app, StopSignal::Terminate).await;
+
+                None
+            }
+            UserInput::Char('k' | 'K') => {
+                self.stop_selected(&app, StopSignal::Kill).await;
+
+                None
+            }
+            UserInput::Char('o' | 'O') => {
+                let mut games = app.games.write().await;
+
+                let path = games.get(self.selected?)?.log_path.clone();
+                games.viewed_log = Some(path);
+
+                Some(AppAction::OpenView(ViewType::GameLog))
+            }
+            _ => {
+                let games = app.games.read().await;
+
+                self.sync_selection(&games.items);
+
+                let action = self.state.on_input(input, games.items.len());
+
+                self.selected = self
+                    .state
+                    .selected()
+                    .and_then(|i| games.items.get(i))
+                    .map(|g| g.id);
+
+                action
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Drawable for GamesView {
+    async fn draw(
+        &mut self,
+        f: &mut Frame<CrosstermBackend<io::Stdout>>,
+        area: Rect,
+        app: Arc<AppState>,
+    ) {
+        let games = app.games.read().await;
+
+        self.sync_selection(&games.items);
+
+        let rows: Vec<Row> = games
+            .items
+            .iter()
+            .map(|g| {
+                let (status, style) = match g.status {
+                    GameStatus::Running => {
+                        ("running".to_owned(), Style::default().fg(Color::Green))
+                    }
+                    GameStatus::Exited(Some(0)) => {
+                        ("exited".to_owned(), Style::default().fg(Color::Yellow))
+                    }
+                    GameStatus::Exited(Some(code)) => (
+                        format!("exited ({})", code),
+                        Style::default().fg(Color::Red),
+                    ),
+                    GameStatus::Exited(None) => {
+                        ("killed".to_owned(), Style::default().fg(Color::Red))
+                    }
+                };
+
+                Row::new(vec![
+                    Cell::from(g.version.to_string()),
+                    Cell::from(
+                        g.address
+                            .as_ref()
+                            .map_or_else(|| "-".to_owned(), ToString::to_string),
+                    ),
+                    Cell::from(g.pid.map_or_else(|| "?".to_owned(), |p| p.to_string())),
+                    Cell::from(format_duration(g.uptime().as_secs())),
+                    Cell::from(status).style(style),
+                ])
+            })
+            .collect();
+
+        let table = Table::new(rows)
+            .header(
+                Row::new(vec!["VERSION", "SERVER", "PID", "UPTIME", "STATUS"]).style(
+                    Style::default()
+                        .fg(Color::Blue)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            )
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("games launched in this session")
+                    .title_alignment(Alignment::Center),
+            )
+            .widths(&[
+                Constraint::Percentage(35),
+                Constraint::Percentage(25),
+                Constraint::Length(8),
+                Constraint::Length(8),
+                Constraint::Length(12),
+            ])
+            .highlight_style(
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::BOLD),
+            );
+
+        f.render_stateful_widget(table, area, &mut self.state.state);
+    }
+}
@@ -1,5 +1,10 @@
+pub mod cleanup;
 pub mod commits;
+pub mod confirm;
+pub mod event_log;
 pub mod events;
+pub mod game_log;
+pub mod games;
 pub mod help;
 pub mod servers;
 pub mod tabs;
@@ -19,12 +24,16 @@ use crate::input::UserInput;
 use crate::states::help::HotKey;
 use crate::states::AppState;
 
-#[derive(PartialEq, Eq, Hash, Debug)]
+#[derive(PartialEq, Eq, Hash, Debug, Clone)]
 pub enum ViewType {
     Tab,
     #[cfg(feature = "geolocation")]
     World,
     Help,
+    Cleanup,
+    Confirm,
+    Events,
+    GameLog,
 }
 
 #[async_trait::async_trait]
@@ -40,6 +49,11 @@ pub trait Drawable {
 #[async_trait::async_trait]
 pub trait InputProcessor {
     async fn on_input(&mut self, input: &UserInput, app: Arc<AppState>) -> Option<AppAction>;
+
+    // view consumes all character input (text field is focused), global hotkeys should be ignored
+    fn captures_input(&self) -> bool {
+        false
+    }
 }
 
 pub trait HotKeys {
@@ -53,3 +67,14 @@ pub trait Named {
 }
 
 pub trait AppView: Drawable + InputProcessor + HotKeys + Named {}
+
+// short human readable duration: 1h05m, 3m20s, 42s
+pub fn format_duration(seconds: u64) -> String {
+    if seconds >= 3600 {
+        format!("{}h{:02}m", seconds / 3600, seconds % 3600 / 60)
+    } else if seconds >= 60 {
+        format!("{}m{:02}s", seconds / 60, seconds % 60)
+    } else {
+        format!("{}s", seconds)
+    }
+}
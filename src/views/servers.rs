@@ -8,30 +8,63 @@ use tui::layout::Rect;
 use tui::terminal::Frame;
 
 use crate::app::AppAction;
-use crate::datatypes::server::Server;
+use crate::datatypes::history::History;
+use crate::datatypes::server::{fuzzy_match, Address, Ping, Server, SortKey, SortOrder};
 use crate::input::UserInput;
 use crate::states::help::HotKey;
-use crate::states::{AppState, StatelessList};
-use crate::views::{Drawable, HotKeys, InputProcessor, Named, ViewType};
+use crate::states::{AppState, ServersState, StatelessList};
+#[cfg(feature = "geolocation")]
+use crate::views::ViewType;
+use crate::views::{Drawable, HotKeys, InputProcessor, Named};
 
 use tui::{
     layout::{Alignment, Constraint, Direction, Layout},
     style::{Color, Modifier, Style},
     symbols::DOT,
     text::{Span, Spans, Text},
-    widgets::{Block, Borders, Paragraph, Row, Table, TableState, Wrap},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Sparkline, Table, TableState, Wrap},
 };
 
+// population history periods, switched with hotkey
+const HISTORY_PERIODS: &[(&str, i64)] = &[("6h", 6), ("24h", 24), ("7d", 24 * 7)];
+
 pub struct ServerView {
     state: StatelessList<TableState>,
+    // list is resorted on every update, index is not enough to keep selection
+    selected: Option<Address>,
+    search: String,
+    searching: bool,
+    // index in HISTORY_PERIODS
+    history_period: usize,
 }
 
 impl ServerView {
     pub fn new() -> Self {
         Self {
             state: StatelessList::new(TableState::default(), false),
+            selected: None,
+            search: String::new(),
+            searching: false,
+            history_period: 1,
+        }
+    }
+
+    // find previously selected server in new list. selection is not dropped if server is
+    // hidden by search so that it comes back once pattern changes
+    fn sync_selection(&mut self, visible: &[&Server]) {
+        match self
+            .selected
+            .as_ref()
+            .and_then(|address| visible.iter().position(|s| &s.address == address))
+        {
+            Some(i) => self.state.select_index(i),
+            None => self.state.unselect(),
         }
     }
+
+    fn selected_server<'a>(&self, visible: &[&'a Server]) -> Option<&'a Server> {
+        self.state.selected().map(|i| visible[i])
+    }
 }
 
 impl Named for ServerView {
@@ -59,6 +92,42 @@ impl HotKeys for ServerView {
                 key: KeyCode::Enter,
                 modifiers: None,
             },
+            HotKey {
+                description: "Pin or unpin selected server",
+                key: KeyCode::Char('p'),
+                modifiers: None,
+            },
+            HotKey {
+                description: "Watch or unwatch selected server population",
+                key: KeyCode::Char('w'),
+                modifiers: None,
+            },
+            HotKey {
+                description: "Change sort column",
+                key: KeyCode::Char('o'),
+                modifiers: None,
+            },
+            HotKey {
+                description: "Reverse sort order",
+                key: KeyCode::Char('r'),
+                modifiers: None,
+            },
+            HotKey {
+                description: "Switch population history period",
+                key: KeyCode::Char('h'),
+                modifiers: None,
+            },
+            HotKey {
+                description: "Export server list to data directory",
+                key: KeyCode::Char('e'),
+                modifiers: None,
+            },
+            HotKey {
+                description:
+                    "Search by name, map, gamemode or fork (Enter to finish, Esc to clear)",
+                key: KeyCode::Char('/'),
+                modifiers: None,
+            },
         ];
 
         hotkeys.append(&mut self.state.hotkeys());
@@ -70,33 +139,126 @@ impl HotKeys for ServerView {
 #[async_trait::async_trait]
 impl InputProcessor for ServerView {
     async fn on_input(&mut self, input: &UserInput, app: Arc<AppState>) -> Option<AppAction> {
+        if self.searching {
+            match input {
+                UserInput::Char(c) => {
+                    self.search.push(*c);
+                    return None;
+                }
+                UserInput::Delete => {
+                    self.search.pop();
+                    return None;
+                }
+                UserInput::Enter => {
+                    self.searching = false;
+                    return None;
+                }
+                UserInput::Back => {
+                    self.search.clear();
+                    self.searching = false;
+                    return None;
+                }
+                _ => {}
+            }
+        }
+
+        if let UserInput::Char('h' | 'H') = input {
+            self.history_period = (self.history_period + 1) % HISTORY_PERIODS.len();
+
+            return None;
+        }
+
+        if let UserInput::Char('e' | 'E') = input {
+            app.watch_task(tokio::spawn(ServersState::export_to_file(
+                app.clone(),
+                app.config.export_format,
+            )))
+            .await;
+
+            return None;
+        }
+
+        let change_sort: Option<fn(SortOrder) -> SortOrder> = match input {
+            UserInput::Char('o' | 'O') => Some(SortOrder::next_key),
+            UserInput::Char('r' | 'R') => Some(SortOrder::reversed),
+            _ => None,
+        };
+
+        if let Some(change_sort) = change_sort {
+            let mut servers = app.servers.write().await;
+            let order = change_sort(servers.sort_order());
+
+            if let Err(err) = servers.set_sort_order(order).await {
+                app.events.read().await.error(err).await;
+            }
+
+            return None;
+        }
+
+        // selection might be hidden by search
+        let selected = self.state.selected().and(self.selected.as_ref());
+
+        if let (UserInput::Char('p' | 'P'), Some(address)) = (input, selected) {
+            if let Err(err) = app.servers.write().await.toggle_pin(address).await {
+                app.events.read().await.error(err).await;
+            }
+
+            return None;
+        }
+
+        if let (UserInput::Char('w' | 'W'), Some(address)) = (input, selected) {
+            if let Err(err) = app
+                .servers
+                .write()
+                .await
+                .toggle_watch(address, app.config.watch_players)
+                .await
+            {
+                app.events.read().await.error(err).await;
+            }
+
+            return None;
+        }
+
+        let servers = app.servers.read().await;
+        let visible = servers.filtered(&self.search);
+
+        self.sync_selection(&visible);
+
         match input {
             #[cfg(feature = "geolocation")]
             UserInput::Char('m' | 'M') => Some(AppAction::OpenView(ViewType::World)),
-            UserInput::Char('i' | 'I') => {
-                if let Some(i) = self.state.selected() {
-                    Some(AppAction::InstallVersion(
-                        app.servers.read().await.items[i].version.clone(),
-                    ))
-                } else {
-                    None
-                }
+            UserInput::Char('/') => {
+                self.searching = true;
+                None
             }
+            UserInput::Char('i' | 'I') => self
+                .selected_server(&visible)
+                .map(|s| AppAction::InstallVersion(s.version.clone())),
             UserInput::Enter => {
-                if let Some(i) = self.state.selected() {
-                    let server = &app.servers.read().await.items[i];
-
-                    Some(AppAction::ConnectToServer {
-                        version: server.version.clone(),
-                        address: server.address.clone(),
+                self.selected_server(&visible)
+                    .map(|s| AppAction::ConnectToServer {
+                        version: s.version.clone(),
+                        address: s.address.clone(),
                     })
-                } else {
-                    None
-                }
             }
-            _ => self.state.on_input(input, app.servers.read().await.count()),
+            UserInput::Back if !self.search.is_empty() => {
+                self.search.clear();
+                None
+            }
+            _ => {
+                let action = self.state.on_input(input, visible.len());
+
+                self.selected = self.selected_server(&visible).map(|s| s.address.clone());
+
+                action
+            }
         }
     }
+
+    fn captures_input(&self) -> bool {
+        self.searching
+    }
 }
 
 #[async_trait::async_trait]
@@ -107,7 +269,11 @@ impl Drawable for ServerView {
         area: Rect,
         app: Arc<AppState>,
     ) {
-        let servers = &app.servers.read().await.items;
+        let servers_lock = app.servers.read().await;
+        let servers = &servers_lock.items;
+        let visible = servers_lock.filtered(&self.search);
+
+        self.sync_selection(&visible);
 
         let mut count_online = 0;
         let mut count_no_players = 0;
@@ -128,15 +294,29 @@ impl Drawable for ServerView {
         }
 
         let chunks = Layout::default()
-            .constraints([Constraint::Min(0), Constraint::Length(5)])
+            .constraints([Constraint::Min(0), Constraint::Length(6)])
             .direction(Direction::Vertical)
             .split(area);
 
-        // "BUILD".len()
-        let mut longest_build_name = 5;
-        let mut longest_map_name = 3;
+        let sort_order = servers_lock.sort_order();
+        let header = |key: SortKey, name: String| {
+            if sort_order.key == key {
+                format!("{} {}", name, sort_order.arrow())
+            } else {
+                name
+            }
+        };
+
+        let name_header = header(SortKey::Name, "NAME".to_owned());
+        let build_header = header(SortKey::Build, "BUILD".to_owned());
+        let map_header = header(SortKey::Map, "MAP".to_owned());
+        let pop_header = header(SortKey::Players, format!("POP [{}]", count_players));
+        let ping_header = header(SortKey::Ping, "PING".to_owned());
 
-        let rows: Vec<Row> = servers
+        let mut longest_build_name = build_header.chars().count();
+        let mut longest_map_name = map_header.chars().count();
+
+        let rows: Vec<Row> = visible
             .iter()
             .map(|s| {
                 if s.version.build.len() > longest_build_name {
@@ -156,33 +336,45 @@ impl Drawable for ServerView {
                     Style::default().fg(Color::Green)
                 };
 
+                let mut name = highlight(&s.name, &self.search);
+                if servers_lock.is_pinned(s) {
+                    name.0
+                        .insert(0, Span::styled("★ ", Style::default().fg(Color::Yellow)));
+                }
+                if servers_lock.is_watched(&app.config, s) {
+                    name.0
+                        .insert(0, Span::styled("◉ ", Style::default().fg(Color::Cyan)));
+                }
+
                 Row::new(vec![
-                    s.name.clone(),
-                    s.version.build.clone(),
-                    s.map.clone(),
-                    s.players.to_string(),
+                    Cell::from(name),
+                    Cell::from(s.version.build.clone()),
+                    Cell::from(highlight(&s.map, &self.search)),
+                    Cell::from(s.players.to_string()),
+                    ping_cell(s.ping),
                 ])
                 .style(style)
             })
             .collect();
 
-        let pop_header = format!("POP [{}]", count_players);
-
         let widths = [
             Constraint::Percentage(60),
             Constraint::Length(longest_build_name as u16),
             // until https://github.com/fdehau/tui-rs/issues/525 is fixed
             Constraint::Length(longest_map_name as u16),
-            Constraint::Length(pop_header.len() as u16),
+            Constraint::Length(pop_header.chars().count() as u16),
+            // "9999ms".len()
+            Constraint::Length(6),
         ];
 
         let table = Table::new(rows)
             .header(
                 Row::new(vec![
-                    "NAME".to_owned(),
-                    "BUILD".to_owned(),
-                    "MAP".to_owned(),
+                    name_header,
+                    build_header,
+                    map_header,
                     pop_header,
+                    ping_header,
                 ])
                 .style(
                     Style::default()
@@ -215,6 +407,25 @@ impl Drawable for ServerView {
                             format!("{} ", count_offline),
                             Style::default().add_modifier(Modifier::BOLD).fg(Color::Red),
                         ),
+                        Span::styled(
+                            // not displayed in columns
+                            if matches!(sort_order.key, SortKey::Fps | SortKey::Address) {
+                                format!("{} by {} {} ", DOT, sort_order.key, sort_order.arrow())
+                            } else {
+                                "".to_owned()
+                            },
+                            Style::default().add_modifier(Modifier::BOLD),
+                        ),
+                        Span::styled(
+                            if self.searching {
+                                format!("{} /{}_ ", DOT, self.search)
+                            } else if !self.search.is_empty() {
+                                format!("{} /{} ", DOT, self.search)
+                            } else {
+                                "".to_owned()
+                            },
+                            Style::default().add_modifier(Modifier::BOLD),
+                        ),
                     ]))
                     .title_alignment(Alignment::Center)
                     .borders(Borders::ALL),
@@ -227,8 +438,27 @@ impl Drawable for ServerView {
             );
 
         // draw server info
-        if let Some(selected) = self.state.selected().map(|s| &servers[s]) {
-            draw_server_info(f, chunks[1], Arc::clone(&app), selected).await;
+        if let Some(selected) = self.selected_server(&visible) {
+            let info_area = if servers_lock.history.enabled() {
+                let chunks = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+                    .split(chunks[1]);
+
+                draw_history(
+                    f,
+                    chunks[1],
+                    &servers_lock.history,
+                    selected,
+                    HISTORY_PERIODS[self.history_period],
+                );
+
+                chunks[0]
+            } else {
+                chunks[1]
+            };
+
+            draw_server_info(f, info_area, Arc::clone(&app), selected).await;
         } else {
             draw_info(f, chunks[1], Arc::clone(&app));
         }
@@ -237,10 +467,86 @@ impl Drawable for ServerView {
     }
 }
 
+fn draw_history(
+    f: &mut Frame<CrosstermBackend<io::Stdout>>,
+    area: Rect,
+    history: &History,
+    selected: &Server,
+    (period_name, hours): (&str, i64),
+) {
+    let period = chrono::Duration::hours(hours);
+    let address = selected.address.to_string();
+
+    // - 2 for borders
+    let data = history.players(&address, period, area.width.saturating_sub(2) as usize);
+
+    let title = match history.stats(&address, period) {
+        Some((peak, average)) => format!(
+            "players {} {} peak {} {} avg {:.1}",
+            period_name, DOT, peak, DOT, average
+        ),
+        None => format!("players {} {} no data", period_name, DOT),
+    };
+
+    f.render_widget(
+        Sparkline::default()
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .data(&data)
+            .style(Style::default().fg(Color::Green)),
+        area,
+    );
+}
+
+fn ping_cell(ping: Option<Ping>) -> Cell<'static> {
+    match ping {
+        Some(ping) => {
+            let ms = ping.smoothed.as_millis();
+
+            Cell::from(format!("{}ms", ms)).style(Style::default().fg(if ms < 100 {
+                Color::Green
+            } else if ms < 200 {
+                Color::Yellow
+            } else {
+                Color::Red
+            }))
+        }
+        None => Cell::from("?"),
+    }
+}
+
+// highlights characters matched by search pattern
+fn highlight<'a>(text: &'a str, pattern: &str) -> Spans<'a> {
+    if pattern.is_empty() {
+        return Spans::from(text);
+    }
+
+    let ranges = match fuzzy_match(text, pattern) {
+        Some(ranges) => ranges,
+        None => return Spans::from(text),
+    };
+
+    let mut spans = Vec::with_capacity(ranges.len() * 2 + 1);
+    let mut last = 0;
+
+    for (start, end) in ranges {
+        spans.push(Span::raw(&text[last..start]));
+        spans.push(Span::styled(
+            &text[start..end],
+            Style::default().add_modifier(Modifier::REVERSED),
+        ));
+
+        last = end;
+    }
+
+    spans.push(Span::raw(&text[last..]));
+
+    Spans::from(spans)
+}
+
 async fn draw_server_info(
     f: &mut Frame<'_, CrosstermBackend<io::Stdout>>,
     area: Rect,
-    app: Arc<AppState>,
+    #[cfg_attr(not(feature = "geolocation"), allow(unused_variables))] app: Arc<AppState>,
     selected: &Server,
 ) {
     #[cfg(feature = "geolocation")]
@@ -266,6 +572,17 @@ async fn draw_server_info(
             format!("address : {}", selected.address),
             format!("location : {}", selected_location),
         ]),
+        Row::new(vec![format!(
+            "ping    : {}",
+            match selected.ping {
+                Some(ping) => format!(
+                    "{}ms (last {}ms)",
+                    ping.smoothed.as_millis(),
+                    ping.latest.as_millis()
+                ),
+                None => "unknown".to_owned(),
+            }
+        )]),
     ];
 
     let table = Table::new(rows)
@@ -23,8 +23,8 @@ use crate::input::UserInput;
 use crate::states::help::HotKey;
 use crate::states::{AppState, StatelessList};
 use crate::views::{
-    commits::CommitView, servers::ServerView, versions::VersionView, AppView, Drawable, HotKeys,
-    InputProcessor, Named,
+    commits::CommitView, games::GamesView, servers::ServerView, versions::VersionView, AppView,
+    Drawable, HotKeys, InputProcessor, Named, ViewType,
 };
 
 #[derive(Copy, Clone)]
@@ -32,6 +32,7 @@ enum Tab {
     Servers,
     Versions,
     Commits,
+    Games,
 }
 
 impl Tab {
@@ -44,11 +45,17 @@ impl Tab {
                 format!("versions [{}]", app.versions.read().await.count())
             }
             Self::Commits => format!("commits [{}]", app.commits.read().await.items.len()),
+            Self::Games => format!("games [{}]", app.games.read().await.running().count()),
         }
     }
 
-    const fn all() -> [Self; 3] {
-        [Self::Servers {}, Self::Versions {}, Self::Commits {}]
+    const fn all() -> [Self; 4] {
+        [
+            Self::Servers {},
+            Self::Versions {},
+            Self::Commits {},
+            Self::Games {},
+        ]
     }
 
     const fn tab_count() -> usize {
@@ -62,6 +69,7 @@ impl From<Tab> for usize {
             Tab::Servers => 0,
             Tab::Versions => 1,
             Tab::Commits => 2,
+            Tab::Games => 3,
         }
     }
 }
@@ -71,6 +79,7 @@ pub struct TabView {
     view_servers: ServerView,
     view_versions: VersionView,
     view_commits: CommitView,
+    view_games: GamesView,
 }
 
 impl TabView {
@@ -84,6 +93,7 @@ impl TabView {
             view_servers: ServerView::new(),
             view_versions: VersionView::new(),
             view_commits: CommitView::new(),
+            view_games: GamesView::new(),
         }
     }
 
@@ -108,6 +118,7 @@ impl Named for TabView {
                 Tab::Servers => self.view_servers.name(),
                 Tab::Versions => self.view_versions.name(),
                 Tab::Commits => self.view_commits.name(),
+                Tab::Games => self.view_games.name(),
             }
         )
     }
@@ -136,12 +147,23 @@ impl HotKeys for TabView {
                 key: KeyCode::Char('c'),
                 modifiers: None,
             },
+            HotKey {
+                description: "Go Games tab",
+                key: KeyCode::Char('g'),
+                modifiers: None,
+            },
+            HotKey {
+                description: "Open event log",
+                key: KeyCode::Char('l'),
+                modifiers: None,
+            },
         ];
 
         hotkeys.append(&mut match self.selected_tab() {
             Tab::Servers => self.view_servers.hotkeys(),
             Tab::Versions => self.view_versions.hotkeys(),
             Tab::Commits => self.view_commits.hotkeys(),
+            Tab::Games => self.view_games.hotkeys(),
         });
 
         hotkeys
@@ -151,6 +173,15 @@ impl HotKeys for TabView {
 #[async_trait::async_trait]
 impl InputProcessor for TabView {
     async fn on_input(&mut self, input: &UserInput, app: Arc<AppState>) -> Option<AppAction> {
+        if self.captures_input() {
+            return match self.selected_tab() {
+                Tab::Servers => self.view_servers.on_input(input, app).await,
+                Tab::Versions => self.view_versions.on_input(input, app).await,
+                Tab::Commits => self.view_commits.on_input(input, app).await,
+                Tab::Games => self.view_games.on_input(input, app).await,
+            };
+        }
+
         match input {
             UserInput::Char('s' | 'S') => {
                 self.select_tab(Tab::Servers);
@@ -164,6 +195,11 @@ impl InputProcessor for TabView {
                 self.select_tab(Tab::Commits);
                 None
             }
+            UserInput::Char('g' | 'G') => {
+                self.select_tab(Tab::Games);
+                None
+            }
+            UserInput::Char('l' | 'L') => Some(AppAction::OpenView(ViewType::Events)),
             UserInput::Tab => {
                 self.state.select_next(Tab::tab_count());
                 None
@@ -174,9 +210,19 @@ impl InputProcessor for TabView {
                 Tab::Servers => self.view_servers.on_input(input, app).await,
                 Tab::Versions => self.view_versions.on_input(input, app).await,
                 Tab::Commits => self.view_commits.on_input(input, app).await,
+                Tab::Games => self.view_games.on_input(input, app).await,
             },
         }
     }
+
+    fn captures_input(&self) -> bool {
+        match self.selected_tab() {
+            Tab::Servers => self.view_servers.captures_input(),
+            Tab::Versions => self.view_versions.captures_input(),
+            Tab::Commits => self.view_commits.captures_input(),
+            Tab::Games => self.view_games.captures_input(),
+        }
+    }
 }
 
 #[async_trait::async_trait]
@@ -224,6 +270,7 @@ impl Drawable for TabView {
             Tab::Servers => self.view_servers.draw(f, chunks[1], app).await,
             Tab::Versions => self.view_versions.draw(f, chunks[1], app).await,
             Tab::Commits => self.view_commits.draw(f, chunks[1], app).await,
+            Tab::Games => self.view_games.draw(f, chunks[1], app).await,
         };
     }
 }
@@ -1,8 +1,11 @@
 use std::io;
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use bytesize::ByteSize;
 
+use chrono::{DateTime, Local, Utc};
+
 use crossterm::event::KeyCode;
 
 use tui::{
@@ -17,12 +20,13 @@ use tui::{
 use crate::app::AppAction;
 use crate::datatypes::{
     game_version::{DownloadUrl, GameVersion},
-    installation::InstallationKind,
+    installation::{Installation, InstallationKind},
+    launch_profile::ProfileSource,
 };
 use crate::input::UserInput;
 use crate::states::help::HotKey;
 use crate::states::{AppState, StatelessList};
-use crate::views::{Drawable, HotKeys, InputProcessor, Named};
+use crate::views::{format_duration, Drawable, HotKeys, InputProcessor, Named, ViewType};
 
 pub struct VersionView {
     state: StatelessList<TableState>,
@@ -60,6 +64,71 @@ impl HotKeys for VersionView {
                 key: KeyCode::Enter,
                 modifiers: None,
             },
+            HotKey {
+                description: "Abort installation (again to discard partial download)",
+                key: KeyCode::Char('a'),
+                modifiers: None,
+            },
+            HotKey {
+                description: "Uninstall selected version",
+                key: KeyCode::Char('d'),
+                modifiers: None,
+            },
+            HotKey {
+                description: "Move queued version up",
+                key: KeyCode::Char('+'),
+                modifiers: None,
+            },
+            HotKey {
+                description: "Move queued version down",
+                key: KeyCode::Char('-'),
+                modifiers: None,
+            },
+            HotKey {
+                description: "Pause/resume queued version",
+                key: KeyCode::Char('p'),
+                modifiers: None,
+            },
+            HotKey {
+                description: "Pin/unpin selected version (never cleaned up)",
+                key: KeyCode::Char('k'),
+                modifiers: None,
+            },
+            HotKey {
+                description: "Preview cleanup of old builds",
+                key: KeyCode::Char('x'),
+                modifiers: None,
+            },
+            HotKey {
+                description: "Lower download rate limit",
+                key: KeyCode::Char('['),
+                modifiers: None,
+            },
+            HotKey {
+                description: "Raise download rate limit",
+                key: KeyCode::Char(']'),
+                modifiers: None,
+            },
+            HotKey {
+                description: "Open output log of last launch",
+                key: KeyCode::Char('o'),
+                modifiers: None,
+            },
+            HotKey {
+                description: "Switch launch profile of selected version",
+                key: KeyCode::Char('r'),
+                modifiers: None,
+            },
+            HotKey {
+                description: "Verify files of selected version",
+                key: KeyCode::Char('y'),
+                modifiers: None,
+            },
+            HotKey {
+                description: "Repair selected version (reinstalls if damaged)",
+                key: KeyCode::Char('u'),
+                modifiers: None,
+            },
         ];
 
         hotkeys.append(&mut self.state.hotkeys());
@@ -73,6 +142,7 @@ enum Progress {
         version: GameVersion,
         progress: u64,
         total: Option<u64>,
+        speed: Option<u64>,
     },
     Unpacking {
         version: GameVersion,
@@ -89,23 +159,45 @@ impl Progress {
         }
     }
 
-    fn label(&self) -> Span {
+    fn label(&self) -> Span<'_> {
         match self {
             Self::Unpacking { version } => Span::styled(
                 format!("unpacking {}: it is a mystery%", version),
                 Style::default().fg(Color::Black),
             ),
             Self::Downloading {
-                version, progress, ..
+                version,
+                progress,
+                total,
+                speed,
             } => {
+                let transfer = match speed {
+                    Some(speed) => {
+                        let eta = match total {
+                            Some(total) if *speed != 0 => {
+                                format_duration(total.saturating_sub(*progress) / speed)
+                            }
+                            _ => "?".to_owned(),
+                        };
+
+                        format!(" {}/s, eta {}", ByteSize::b(*speed), eta)
+                    }
+                    None => String::new(),
+                };
+
                 if let Some(ratio) = self.ratio() {
                     Span::styled(
-                        format!("downloading {}: {:.2}%", version, ratio * 100.0),
+                        format!("downloading {}: {:.2}%{}", version, ratio * 100.0, transfer),
                         Style::default().fg(Color::Black),
                     )
                 } else {
                     Span::styled(
-                        format!("downloading {}: {} / ?", version, ByteSize::b(*progress)),
+                        format!(
+                            "downloading {}: {} / ?{}",
+                            version,
+                            ByteSize::b(*progress),
+                            transfer
+                        ),
                         Style::default().fg(Color::Black),
                     )
                 }
@@ -114,6 +206,14 @@ impl Progress {
     }
 }
 
+fn format_rate(rate: u64) -> String {
+    if rate == 0 {
+        "unlimited".to_owned()
+    } else {
+        format!("{}/s", ByteSize::b(rate).to_string_as(true))
+    }
+}
+
 #[async_trait::async_trait]
 impl InputProcessor for VersionView {
     async fn on_input(&mut self, input: &UserInput, app: Arc<AppState>) -> Option<AppAction> {
@@ -140,6 +240,24 @@ impl InputProcessor for VersionView {
                     None
                 }
             }
+            UserInput::Char('y' | 'Y') => {
+                if let Some(i) = self.state.selected() {
+                    Some(AppAction::VerifyVersion(
+                        app.versions.read().await.items[i].version.clone(),
+                    ))
+                } else {
+                    None
+                }
+            }
+            UserInput::Char('u' | 'U') => {
+                if let Some(i) = self.state.selected() {
+                    Some(AppAction::RepairVersion(
+                        app.versions.read().await.items[i].version.clone(),
+                    ))
+                } else {
+                    None
+                }
+            }
             UserInput::Char('d' | 'D') => {
                 if let Some(i) = self.state.selected() {
                     Some(AppAction::UninstallVersion(
@@ -158,6 +276,100 @@ impl InputProcessor for VersionView {
                     None
                 }
             }
+            UserInput::Char(c @ ('+' | '-')) => {
+                if let Some(i) = self.state.selected() {
+                    let mut versions = app.versions.write().await;
+                    let version = versions.items[i].version.clone();
+
+                    versions.move_in_queue(&version, *c == '+');
+                }
+
+                None
+            }
+            UserInput::Char('p' | 'P') => {
+                if let Some(i) = self.state.selected() {
+                    let mut versions = app.versions.write().await;
+                    let version = versions.items[i].version.clone();
+
+                    versions.toggle_pause(&version);
+                }
+
+                None
+            }
+            UserInput::Char('k' | 'K') => {
+                if let Some(i) = self.state.selected() {
+                    let mut versions = app.versions.write().await;
+                    let version = versions.items[i].version.clone();
+
+                    if let Err(err) = versions.toggle_pin(&version).await {
+                        app.events.read().await.error(err).await;
+                    }
+                }
+
+                None
+            }
+            UserInput::Char('r' | 'R') => {
+                if let Some(i) = self.state.selected() {
+                    let mut versions = app.versions.write().await;
+                    let version = versions.items[i].version.clone();
+
+                    match versions.cycle_profile(&app.config, &version).await {
+                        Ok(Some(profile)) => {
+                            app.events
+                                .read()
+                                .await
+                                .event(&format!("{} will use profile {}", version, profile))
+                                .await
+                        }
+                        Ok(None) => {
+                            app.events
+                                .read()
+                                .await
+                                .event(&format!("{} will use fork or default profile", version))
+                                .await
+                        }
+                        Err(err) => app.events.read().await.error(err).await,
+                    }
+                }
+
+                None
+            }
+            UserInput::Char('x' | 'X') => Some(AppAction::OpenView(ViewType::Cleanup)),
+            UserInput::Char('o' | 'O') => {
+                let version = app.versions.read().await.items[self.state.selected()?]
+                    .version
+                    .clone();
+
+                let mut games = app.games.write().await;
+
+                match games.latest_log(&app.config.dirs.logs_dir, &version) {
+                    Some(path) => {
+                        games.viewed_log = Some(path);
+
+                        Some(AppAction::OpenView(ViewType::GameLog))
+                    }
+                    None => {
+                        app.events
+                            .read()
+                            .await
+                            .event(&format!("{} was not launched yet", version))
+                            .await;
+
+                        None
+                    }
+                }
+            }
+            UserInput::Char(c @ ('[' | ']')) => {
+                let rate = app.versions.read().await.rate_limiter.step(*c == ']');
+
+                app.events
+                    .read()
+                    .await
+                    .event(&format!("Download rate limit: {}", format_rate(rate)))
+                    .await;
+
+                None
+            }
             UserInput::Enter => {
                 if let Some(i) = self.state.selected() {
                     Some(AppAction::LaunchVersion(
@@ -182,16 +394,26 @@ impl Drawable for VersionView {
         area: Rect,
         app: Arc<AppState>,
     ) {
-        let versions = &app.versions.read().await.items;
+        let versions = app.versions.read().await;
 
         let mut total_size = 0;
         let mut in_progress = Vec::new();
 
         let items: Vec<Row> = versions
+            .items
             .iter()
             .map(|i| {
                 Row::new(vec![
-                    format!("{}-{}", i.version.fork, i.version.build),
+                    format!(
+                        "{}{}-{}",
+                        if versions.is_pinned(&i.version) {
+                            "★ "
+                        } else {
+                            ""
+                        },
+                        i.version.fork,
+                        i.version.build
+                    ),
                     match &i.kind {
                         InstallationKind::Discovered => {
                             format!(
@@ -203,20 +425,40 @@ impl Drawable for VersionView {
                                 }
                             )
                         }
-                        InstallationKind::Downloading { progress, total } => {
+                        InstallationKind::Queued { paused } => {
+                            let position = versions
+                                .queue_position(&i.version)
+                                .map_or_else(String::new, |p| format!(" #{}", p + 1));
+
+                            if *paused {
+                                format!("paused{}", position)
+                            } else {
+                                format!("queued{}", position)
+                            }
+                        }
+                        InstallationKind::Downloading {
+                            progress,
+                            total,
+                            speed,
+                        } => {
                             total_size += progress;
 
                             in_progress.push(Progress::Downloading {
                                 version: i.version.clone(),
                                 progress: *progress,
                                 total: *total,
+                                speed: *speed,
                             });
                             "downloading".to_owned()
                         }
-                        InstallationKind::Installed { size, .. } => {
+                        InstallationKind::Installed { size, manifest } => {
                             total_size += size;
 
-                            "installed".to_owned()
+                            if manifest.is_some() {
+                                "installed".to_owned()
+                            } else {
+                                "installed (legacy)".to_owned()
+                            }
                         }
                         InstallationKind::Unpacking => {
                             in_progress.push(Progress::Unpacking {
@@ -224,9 +466,10 @@ impl Drawable for VersionView {
                             });
                             "unpacking".to_owned()
                         }
+                        InstallationKind::Broken { .. } => "broken".to_owned(),
                     },
                     match &i.kind {
-                        InstallationKind::Installed { size } => ByteSize::b(*size).to_string(),
+                        InstallationKind::Installed { size, .. } => ByteSize::b(*size).to_string(),
                         InstallationKind::Downloading { progress, .. } => {
                             ByteSize::b(*progress).to_string()
                         }
@@ -236,12 +479,21 @@ impl Drawable for VersionView {
             })
             .collect();
 
+        let selected = self
+            .state
+            .selected()
+            .and_then(|i| versions.items.iter().nth(i));
+
         let mut constraints = vec![Constraint::Min(0)];
 
         if !in_progress.is_empty() {
             constraints.push(Constraint::Length(2 + in_progress.len() as u16));
         }
 
+        if selected.is_some() {
+            constraints.push(Constraint::Length(9));
+        }
+
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints(constraints.clone())
@@ -274,6 +526,12 @@ impl Drawable for VersionView {
 
         f.render_stateful_widget(table, chunks[0], &mut self.state.state);
 
+        if let Some(selected) = selected {
+            let profile = versions.launch_profile(&app.config, &selected.version);
+
+            draw_version_info(f, chunks[chunks.len() - 1], &app, selected, profile);
+        }
+
         if !in_progress.is_empty() {
             let mut progress_bars_constraints = Vec::new();
 
@@ -284,7 +542,10 @@ impl Drawable for VersionView {
 
             f.render_widget(
                 Block::default()
-                    .title("PROGRESS")
+                    .title(format!(
+                        "PROGRESS [limit: {}]",
+                        format_rate(versions.rate_limiter.rate())
+                    ))
                     .title_alignment(Alignment::Center)
                     .borders(Borders::ALL),
                 chunks[1],
@@ -318,3 +579,83 @@ impl Drawable for VersionView {
         }
     }
 }
+
+fn draw_version_info(
+    f: &mut Frame<CrosstermBackend<io::Stdout>>,
+    area: Rect,
+    app: &AppState,
+    selected: &Installation,
+    profile: Option<(&str, ProfileSource)>,
+) {
+    let format_time = |time: &DateTime<Utc>| {
+        time.with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    };
+
+    let path = app
+        .config
+        .dirs
+        .installations_dir
+        .join(PathBuf::from(selected.version.clone()));
+
+    let mut rows = match &selected.kind {
+        InstallationKind::Installed {
+            manifest: Some(manifest),
+            ..
+        } => vec![
+            format!("path      : {}", path.display()),
+            format!("installed : {}", format_time(&manifest.installed_at)),
+            format!(
+                "launched  : {}",
+                manifest
+                    .last_launched
+                    .as_ref()
+                    .map_or_else(|| "never".to_owned(), format_time)
+            ),
+            format!(
+                "server    : {}",
+                manifest.server.as_deref().unwrap_or("unknown")
+            ),
+            format!("url       : {}", manifest.url),
+            format!("sha256    : {}", manifest.sha256),
+        ],
+        InstallationKind::Installed { manifest: None, .. } => vec![
+            format!("path      : {}", path.display()),
+            "legacy installation, no manifest".to_owned(),
+        ],
+        InstallationKind::Broken { reason } => vec![
+            format!("path      : {}", path.display()),
+            format!("broken    : {}", reason),
+        ],
+        _ => vec![format!(
+            "url       : {}",
+            match &selected.version.download {
+                DownloadUrl::Valid(url) | DownloadUrl::Untrusted(url) => url.to_string(),
+                DownloadUrl::Invalid(bad) => bad.clone(),
+                DownloadUrl::Local => "unknown".to_owned(),
+            }
+        )],
+    };
+
+    rows.push(format!(
+        "profile   : {}",
+        profile.map_or_else(
+            || "none".to_owned(),
+            |(name, source)| format!("{} ({})", name, source)
+        )
+    ));
+
+    let table = Table::new(rows.into_iter().map(|row| Row::new(vec![row])))
+        .block(
+            Block::default().borders(Borders::ALL).title(Span::styled(
+                selected.version.to_string(),
+                Style::default()
+                    .add_modifier(Modifier::BOLD)
+                    .fg(Color::Blue),
+            )),
+        )
+        .widths(&[Constraint::Percentage(100)]);
+
+    f.render_widget(table, area);
+}